@@ -1,9 +1,11 @@
 #!/usr/bin/env node
+import { createHash } from 'node:crypto';
 import http, { createServer } from 'node:http';
 import https from 'node:https';
+import tls from 'node:tls';
 import dns from 'node:dns/promises';
 import { existsSync, readFileSync, writeFileSync } from 'node:fs';
-import { readdir } from 'node:fs/promises';
+import { chmod, mkdir, readdir, rename, writeFile } from 'node:fs/promises';
 import { promisify } from 'node:util';
 import { brotliCompress, gzipSync } from 'node:zlib';
 import path from 'node:path';
@@ -59,6 +61,68 @@ function isTransientVerificationError(error) {
   return /timed out|timeout|network|fetch failed|failed to fetch|socket hang up/i.test(error.message);
 }
 
+// ── Egress proxy ─────────────────────────────────────────────────────────
+// When the desktop host enables egress control it sets HTTP(S)_PROXY to its
+// loopback CONNECT proxy. node:http(s) ignores those variables, so every
+// outbound request below opens its own tunnel. Plain http is tunneled too:
+// the host proxy only speaks CONNECT.
+
+function egressProxyFor(url) {
+  const raw = url.protocol === 'https:'
+    ? (process.env.HTTPS_PROXY || process.env.https_proxy)
+    : (process.env.HTTP_PROXY || process.env.http_proxy);
+  if (!raw) return null;
+  const host = url.hostname.replace(/^\[|\]$/g, '').toLowerCase();
+  const noProxy = String(process.env.NO_PROXY || process.env.no_proxy || '')
+    .split(',').map((entry) => entry.trim().toLowerCase().replace(/^\./, '')).filter(Boolean);
+  if (noProxy.some((entry) => entry === '*' || host === entry || host.endsWith(`.${entry}`))) return null;
+  try { return new URL(raw); } catch { return null; }
+}
+
+function connectThroughProxy(proxy, url, timeoutMs = 15000) {
+  const port = url.port || (url.protocol === 'https:' ? 443 : 80);
+  const target = `${url.hostname}:${port}`;
+  const headers = { Host: target };
+  if (proxy.username) {
+    const credentials = `${decodeURIComponent(proxy.username)}:${decodeURIComponent(proxy.password)}`;
+    headers['Proxy-Authorization'] = `Basic ${Buffer.from(credentials).toString('base64')}`;
+  }
+  return new Promise((resolve, reject) => {
+    const req = http.request({
+      hostname: proxy.hostname,
+      port: proxy.port || 80,
+      method: 'CONNECT',
+      path: target,
+      headers,
+    });
+    req.once('connect', (res, socket) => {
+      if (res.statusCode === 200) {
+        resolve(socket);
+        return;
+      }
+      socket.destroy();
+      const error = new Error(`Egress proxy refused ${target} (${res.statusCode})`);
+      error.code = res.statusCode === 403 ? 'EGRESS_BLOCKED' : 'EGRESS_PROXY_ERROR';
+      reject(error);
+    });
+    req.once('error', reject);
+    req.setTimeout(timeoutMs, () => req.destroy(new Error(`Egress proxy CONNECT to ${target} timed out`)));
+    req.end();
+  });
+}
+
+/** Request options that send the request over a proxy tunnel, or {} when direct. */
+async function egressRequestOptions(url) {
+  const proxy = egressProxyFor(url);
+  if (!proxy) return {};
+  const socket = await connectThroughProxy(proxy, url);
+  const servername = url.hostname.replace(/^\[|\]$/g, '');
+  return {
+    agent: undefined,
+    createConnection: () => (url.protocol === 'https:' ? tls.connect({ socket, servername }) : socket),
+  };
+}
+
 globalThis.fetch = async function ipv4Fetch(input, init) {
   const isRequest = input && typeof input === 'object' && 'url' in input;
   let url;
@@ -74,8 +138,9 @@ globalThis.fetch = async function ipv4Fetch(input, init) {
       : Array.isArray(rawHeaders) ? Object.fromEntries(rawHeaders) : rawHeaders;
     Object.assign(headers, h);
   }
+  const egress = await egressRequestOptions(url);
   return new Promise((resolve, reject) => {
-    const req = mod.request({ hostname: url.hostname, port: url.port || (url.protocol === 'https:' ? 443 : 80), path: url.pathname + url.search, method, headers, family: 4 }, (res) => {
+    const req = mod.request({ hostname: url.hostname, port: url.port || (url.protocol === 'https:' ? 443 : 80), path: url.pathname + url.search, method, headers, family: 4, ...egress }, (res) => {
       const chunks = [];
       res.on('data', (c) => chunks.push(c));
       res.on('end', () => {
@@ -107,6 +172,57 @@ const ALLOWED_ENV_KEYS = new Set([
   'OLLAMA_API_URL', 'OLLAMA_MODEL', 'WORLDMONITOR_API_KEY', 'WTO_API_KEY',
 ]);
 
+// ── Host bridge ──────────────────────────────────────────────────────────
+// Reverse channel to the desktop host. The host passes its loopback address
+// and a per-launch token at spawn, and checks its capability allowlist on
+// every call, so allowlist changes apply without a restart. Handlers reach
+// it through globalThis.__WORLD_MONITOR_HOST__, which is undefined outside
+// the desktop app.
+
+export class HostBridgeError extends Error {
+  constructor(message, code, status = null) {
+    super(message);
+    this.name = 'HostBridgeError';
+    this.code = code;
+    this.status = status;
+  }
+}
+
+export async function callHost(method, params = {}, timeoutMs = 5000) {
+  const baseUrl = process.env.LOCAL_API_HOST_URL;
+  const token = process.env.LOCAL_API_HOST_TOKEN;
+  if (!baseUrl || !token) {
+    throw new HostBridgeError('Host bridge not available', 'HOST_UNAVAILABLE');
+  }
+  let response;
+  try {
+    response = await _originalFetch(`${baseUrl}/call`, {
+      method: 'POST',
+      headers: { Authorization: `Bearer ${token}`, 'Content-Type': 'application/json' },
+      body: JSON.stringify({ method, params }),
+      signal: AbortSignal.timeout(timeoutMs),
+    });
+  } catch (error) {
+    throw new HostBridgeError(`Host bridge unreachable: ${error.message}`, 'HOST_UNAVAILABLE');
+  }
+  const payload = await response.json().catch(() => ({}));
+  if (!response.ok) {
+    const code = response.status === 403 ? 'HOST_CAPABILITY_DENIED' : 'HOST_CALL_FAILED';
+    throw new HostBridgeError(payload.error || `Host call failed (${response.status})`, code, response.status);
+  }
+  return payload.result;
+}
+
+export const hostBridge = Object.freeze({
+  getSecret: async (key) => (await callHost('secrets.get', { key }))?.value ?? null,
+  notify: (title, body = '') => callHost('notify', { title, body }),
+  readCache: async (key) => (await callHost('cache.read', { key }))?.value ?? null,
+});
+
+if (process.env.LOCAL_API_HOST_URL) {
+  globalThis.__WORLD_MONITOR_HOST__ = hostBridge;
+}
+
 const CHROME_UA = 'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36';
 
 // ── SSRF protection ──────────────────────────────────────────────────────
@@ -390,6 +506,9 @@ const moduleCache = new Map();
 const failedImports = new Set();
 const fallbackCounts = new Map();
 const cloudPreferred = new Set();
+const importErrors = new Map();
+const MISSING_HANDLERS_MAX = 100;
+const missingHandlers = new Set();
 
 const TRAFFIC_LOG_MAX = 200;
 const trafficLog = [];
@@ -440,8 +559,10 @@ async function importHandler(modulePath) {
   try {
     const mod = await import(pathToFileURL(modulePath).href);
     moduleCache.set(modulePath, mod);
+    importErrors.delete(modulePath);
     return mod;
   } catch (error) {
+    importErrors.set(modulePath, error.code === 'ERR_MODULE_NOT_FOUND' ? `missing dependency: ${error.message}` : error.message);
     if (error.code === 'ERR_MODULE_NOT_FOUND') {
       failedImports.add(modulePath);
     }
@@ -449,6 +570,31 @@ async function importHandler(modulePath) {
   }
 }
 
+// ── Cloud fallback policy ────────────────────────────────────────────────
+// Set by the desktop host at spawn and through /api/local-fallback-policy.
+// `never` keeps every request on this machine; `allowlist` only lets the
+// listed paths (`/api/news/*` is a prefix) or route patterns reach the cloud.
+const FALLBACK_MODES = new Set(['never', 'always', 'allowlist']);
+
+export function parseFallbackPolicy(mode, allowlist) {
+  const raw = String(mode ?? '').trim().toLowerCase();
+  // `true` is the original on/off switch.
+  const normalized = raw === 'true' ? 'always' : FALLBACK_MODES.has(raw) ? raw : 'never';
+  const entries = Array.isArray(allowlist) ? allowlist : String(allowlist ?? '').split(',');
+  return {
+    mode: normalized,
+    allowlist: entries.map(entry => String(entry).trim()).filter(Boolean),
+  };
+}
+
+export function fallbackAllowed(policy, pathname, route) {
+  if (policy.mode === 'always') return true;
+  if (policy.mode !== 'allowlist') return false;
+  return policy.allowlist.some(entry => entry === pathname
+    || entry === route
+    || (entry.endsWith('*') && pathname.startsWith(entry.slice(0, -1))));
+}
+
 function resolveConfig(options = {}) {
   const port = Number(options.port ?? process.env.LOCAL_API_PORT ?? 46123);
   const remoteBase = String(options.remoteBase ?? process.env.LOCAL_API_REMOTE_BASE ?? 'https://worldmonitor.app').replace(/\/$/, '');
@@ -461,8 +607,17 @@ function resolveConfig(options = {}) {
     ].find((candidate) => existsSync(candidate)) ?? path.join(resourceDir, 'api');
   const dataDir = String(options.dataDir ?? process.env.LOCAL_API_DATA_DIR ?? resourceDir);
   const mode = String(options.mode ?? process.env.LOCAL_API_MODE ?? 'desktop-sidecar');
-  const cloudFallback = String(options.cloudFallback ?? process.env.LOCAL_API_CLOUD_FALLBACK ?? '') === 'true';
+  const fallbackPolicy = parseFallbackPolicy(
+    options.cloudFallback ?? process.env.LOCAL_API_CLOUD_FALLBACK,
+    options.cloudFallbackAllowlist ?? process.env.LOCAL_API_CLOUD_FALLBACK_ALLOWLIST,
+  );
   const logger = options.logger ?? console;
+  const onShutdownRequest = typeof options.onShutdownRequest === 'function' ? options.onShutdownRequest : null;
+  // A pinned port must fail loudly instead of silently moving elsewhere.
+  const strictPort = String(options.strictPort ?? process.env.LOCAL_API_PORT_STRICT ?? '') === 'true';
+  // Unix domain socket path (or Windows named pipe); replaces the TCP listener.
+  const socketPath = options.socketPath ?? process.env.LOCAL_API_SOCKET ?? null;
+  const recordDir = options.recordDir ?? process.env.LOCAL_API_RECORD_DIR ?? null;
 
   return {
     port,
@@ -471,9 +626,174 @@ function resolveConfig(options = {}) {
     dataDir,
     apiDir,
     mode,
-    cloudFallback,
+    fallbackPolicy,
     logger,
+    onShutdownRequest,
+    strictPort,
+    socketPath: socketPath ? String(socketPath) : null,
+    recordDir: recordDir ? String(recordDir) : null,
+  };
+}
+
+// One-line handshake the desktop host reads from our stdout to learn the
+// bound port (or why binding failed). Only emitted when the host asks for it.
+const HANDSHAKE_PREFIX = 'LOCAL_API_HANDSHAKE ';
+
+function writeHandshake(payload) {
+  if (process.env.LOCAL_API_HANDSHAKE !== 'stdout') return;
+  process.stdout.write(`${HANDSHAKE_PREFIX}${JSON.stringify(payload)}\n`);
+}
+
+// One line per /api/* request for the host's per-route metrics and HAR export.
+const METRIC_PREFIX = 'LOCAL_API_METRIC ';
+const REQUEST_UPSTREAM = Symbol('requestUpstream');
+const REQUEST_FALLBACK = Symbol('requestFallback');
+const BUILTIN_ROUTES = new Set([
+  '/api/service-status', '/api/local-status', '/api/local-traffic-log',
+  '/api/local-debug-toggle', '/api/local-shutdown', '/api/register-interest',
+  '/api/rss-proxy', '/api/local-env-update', '/api/local-validate-secret',
+  '/api/local-routes', '/api/local-fallback-policy',
+]);
+
+// Route patterns rather than raw paths, so metric labels stay bounded.
+function metricRoute(pathname, routes) {
+  if (BUILTIN_ROUTES.has(pathname)) return pathname;
+  const apiPath = pathname.slice(4) || '/';
+  const match = routes.find(candidate => matchRoute(candidate.routePath, apiPath));
+  return match ? `/api/${match.routePath}` : 'unmatched';
+}
+
+// ── Route table introspection ───────────────────────────────────────────
+// Reports how each route is being served: `local`, `cloud` once a failing
+// local handler has been replaced by the cloud, `broken`, or `not-loaded`
+// for handlers no request has imported yet. Handlers stay lazily imported
+// unless `probe` asks to import them all.
+function cloudPreferredPaths(route, routes) {
+  return [...cloudPreferred].filter(pathname => pickModule(pathname, routes) === route.modulePath);
+}
+
+function routeLoadError(modulePath) {
+  const mod = moduleCache.get(modulePath);
+  if (mod) return typeof mod.default === 'function' ? null : 'module has no default export function';
+  if (importErrors.has(modulePath)) return importErrors.get(modulePath);
+  if (failedImports.has(modulePath)) return 'import failed';
+  return undefined;
+}
+
+async function describeRoutes(routes, context, { probe = false } = {}) {
+  const entries = [];
+  for (const route of routes) {
+    if (probe && !moduleCache.has(route.modulePath)) {
+      try { await importHandler(route.modulePath); } catch { /* recorded in importErrors */ }
+    }
+    const error = routeLoadError(route.modulePath);
+    const cloudPaths = cloudPreferredPaths(route, routes);
+    let status = 'local';
+    if (error === undefined) status = 'not-loaded';
+    else if (error) status = 'broken';
+    else if (cloudPaths.length > 0) status = 'cloud';
+    entries.push({
+      route: `/api/${route.routePath}`,
+      priority: routePriority(route.routePath),
+      module: path.relative(context.apiDir, route.modulePath).replace(/\\/g, '/'),
+      status,
+      error: error ?? null,
+      cloudPaths,
+    });
+  }
+  return {
+    apiDir: context.apiDir,
+    cloudFallback: context.fallbackPolicy.mode,
+    routes: entries,
+    missing: [...missingHandlers],
+  };
+}
+
+// ── Fixture recording ────────────────────────────────────────────────────
+// With LOCAL_API_RECORD_DIR set, every request/response pair is saved as one
+// JSON fixture that the desktop host can replay without starting the sidecar.
+// The format is shared with src-tauri/src/local_api_capture.rs.
+const FIXTURE_VERSION = 1;
+const FIXTURE_SKIPPED_HEADERS = new Set([
+  'content-length', 'transfer-encoding', 'connection', 'keep-alive', 'vary',
+  'access-control-allow-origin', 'access-control-allow-methods',
+  'access-control-allow-headers', 'access-control-max-age',
+]);
+
+// Parameter order does not matter when matching a fixture.
+export function canonicalQuery(search) {
+  return String(search || '').replace(/^\?/, '').split('&').filter(Boolean).sort().join('&');
+}
+
+export function fixtureFileName(method, pathname, query, requestBody) {
+  const digest = createHash('sha256')
+    .update(`${method} ${pathname}?${query}\n`)
+    .update(requestBody || Buffer.alloc(0))
+    .digest('hex')
+    .slice(0, 16);
+  const slug = pathname.replace(/^\/api\//, '').replace(/[^a-zA-Z0-9]+/g, '-').replace(/^-|-$/g, '').slice(0, 80);
+  return `${method}-${slug || 'root'}-${digest}.json`;
+}
+
+async function recordFixture(context, req, requestUrl, status, headers, body) {
+  const requestBody = req[REQUEST_BODY_CACHE] || null;
+  const query = canonicalQuery(requestUrl.search);
+  const fixture = {
+    version: FIXTURE_VERSION,
+    recordedAt: new Date().toISOString(),
+    request: {
+      method: req.method,
+      path: requestUrl.pathname,
+      query,
+      body: requestBody ? requestBody.toString('base64') : null,
+    },
+    response: {
+      status,
+      headers: Object.fromEntries(Object.entries(headers).filter(([name]) => !FIXTURE_SKIPPED_HEADERS.has(name.toLowerCase()))),
+      body: body.toString('base64'),
+    },
   };
+  const target = path.join(context.recordDir, fixtureFileName(req.method, requestUrl.pathname, query, requestBody));
+  try {
+    await mkdir(context.recordDir, { recursive: true });
+    // Write then rename so a replay never reads a half-written fixture.
+    await writeFile(`${target}.tmp`, JSON.stringify(fixture, null, 2));
+    await rename(`${target}.tmp`, target);
+  } catch (error) {
+    context.logger.warn(`[local-api] failed to record fixture for ${requestUrl.pathname}: ${error.message}`);
+  }
+}
+
+function headerPairs(headers) {
+  return Object.entries(headers).flatMap(([name, value]) => (
+    Array.isArray(value) ? value.map(v => [name, String(v)]) : [[name, String(value)]]
+  ));
+}
+
+// Headers go to the host unredacted; it masks them before keeping them.
+function writeMetric(req, requestUrl, routes, { status, startedAt, bytes, responseHeaders }) {
+  if (process.env.LOCAL_API_METRICS !== 'stdout' || req.method === 'OPTIONS') return;
+  const route = metricRoute(requestUrl.pathname, routes);
+  // Built-in endpoints answer themselves unless the cloud served them.
+  const upstream = req[REQUEST_UPSTREAM] || 'sidecar';
+  const rawHeaders = req.rawHeaders || [];
+  const requestHeaders = [];
+  for (let i = 0; i + 1 < rawHeaders.length; i += 2) requestHeaders.push([rawHeaders[i], rawHeaders[i + 1]]);
+  process.stdout.write(`${METRIC_PREFIX}${JSON.stringify({
+    route,
+    upstream,
+    status,
+    durationMs: Date.now() - startedAt,
+    bytes,
+    method: req.method,
+    url: requestUrl.href,
+    startedAt: new Date(startedAt).toISOString(),
+    httpVersion: `HTTP/${req.httpVersion || '1.1'}`,
+    requestHeaders,
+    requestBytes: req[REQUEST_BODY_CACHE]?.length ?? 0,
+    responseHeaders: headerPairs(responseHeaders),
+    fallback: req[REQUEST_FALLBACK] ?? null,
+  })}\n`);
 }
 
 function isMainModule() {
@@ -487,30 +807,42 @@ async function handleLocalServiceStatus(context) {
     timestamp: new Date().toISOString(),
     summary: { operational: 2, degraded: 0, outage: 0, unknown: 0 },
     services: [
-      { id: 'local-api', name: 'Local Desktop API', category: 'dev', status: 'operational', description: `Running on 127.0.0.1:${context.port}` },
+      { id: 'local-api', name: 'Local Desktop API', category: 'dev', status: 'operational', description: context.socketPath ? `Running on socket ${context.socketPath}` : `Running on 127.0.0.1:${context.port}` },
       { id: 'cloud-pass-through', name: 'Cloud pass-through', category: 'cloud', status: 'operational', description: `Fallback target ${context.remoteBase}` },
     ],
-    local: { enabled: true, mode: context.mode, port: context.port, remoteBase: context.remoteBase },
+    local: { enabled: true, mode: context.mode, port: context.socketPath ? null : context.port, socket: context.socketPath, remoteBase: context.remoteBase },
   });
 }
 
-async function tryCloudFallback(requestUrl, req, context, reason) {
+async function tryCloudFallback(requestUrl, req, routes, context, reason) {
+  const brief = reason instanceof Error
+    ? (reason.code === 'ERR_MODULE_NOT_FOUND' ? 'missing npm dependency' : reason.message)
+    : (reason || 'cloud preferred');
+  if (!fallbackAllowed(context.fallbackPolicy, requestUrl.pathname, metricRoute(requestUrl.pathname, routes))) {
+    // Only a real local failure counts as a refused fallback.
+    if (reason) {
+      req[REQUEST_FALLBACK] = { outcome: 'blocked', reason: brief };
+      logOnce(context.logger, requestUrl.pathname, `cloud fallback blocked by policy (${brief})`);
+    }
+    return null;
+  }
   if (reason) {
     const route = requestUrl.pathname;
     const count = (fallbackCounts.get(route) || 0) + 1;
     fallbackCounts.set(route, count);
     if (count === 1) {
-      const brief = reason instanceof Error
-        ? (reason.code === 'ERR_MODULE_NOT_FOUND' ? 'missing npm dependency' : reason.message)
-        : reason;
       context.logger.warn(`[local-api] ${route} → cloud (${brief})`);
     } else if (count === 5 || count % 100 === 0) {
       context.logger.warn(`[local-api] ${route} → cloud x${count}`);
     }
   }
   try {
-    return await proxyToCloud(requestUrl, req, context.remoteBase);
+    const response = await proxyToCloud(requestUrl, req, context.remoteBase);
+    req[REQUEST_UPSTREAM] = 'cloud';
+    req[REQUEST_FALLBACK] = { outcome: 'served', reason: brief };
+    return response;
   } catch (error) {
+    req[REQUEST_FALLBACK] = { outcome: 'failed', reason: `${brief}; cloud: ${error.message}` };
     context.logger.error('[local-api] cloud fallback failed', requestUrl.pathname, error);
     return null;
   }
@@ -548,6 +880,7 @@ async function fetchWithTimeout(url, options = {}, timeoutMs = 12000) {
   // first and some servers (EIA, NASA FIRMS) have broken IPv6 causing ETIMEDOUT.
   const u = new URL(url);
   if (u.protocol === 'https:') {
+    const egress = await egressRequestOptions(u);
     return new Promise((resolve, reject) => {
       const reqOpts = {
         hostname: u.hostname,
@@ -556,10 +889,12 @@ async function fetchWithTimeout(url, options = {}, timeoutMs = 12000) {
         method: options.method || 'GET',
         headers: options.headers || {},
         family: 4,
+        ...egress,
       };
       // Pin to a pre-resolved IP to prevent TOCTOU DNS rebinding.
       // The hostname is kept for SNI / TLS certificate validation.
-      if (options.resolvedAddress) {
+      // A proxied request resolves at the proxy instead.
+      if (options.resolvedAddress && !egress.createConnection) {
         reqOpts.lookup = (_hostname, _opts, cb) => cb(null, options.resolvedAddress, 4);
       }
       const req = https.request(reqOpts, (res) => {
@@ -590,7 +925,7 @@ async function fetchWithTimeout(url, options = {}, timeoutMs = 12000) {
   // validated IP and set the Host header so virtual-host routing still works.
   let fetchUrl = url;
   const fetchHeaders = { ...(options.headers || {}) };
-  if (options.resolvedAddress && u.protocol === 'http:') {
+  if (options.resolvedAddress && u.protocol === 'http:' && !egressProxyFor(u)) {
     const pinned = new URL(url);
     fetchHeaders['Host'] = pinned.host;
     pinned.hostname = options.resolvedAddress;
@@ -940,12 +1275,43 @@ async function dispatch(requestUrl, req, routes, context) {
       success: true,
       mode: context.mode,
       port: context.port,
+      socket: context.socketPath,
       apiDir: context.apiDir,
       remoteBase: context.remoteBase,
-      cloudFallback: context.cloudFallback,
+      cloudFallback: context.fallbackPolicy.mode,
       routes: routes.length,
     });
   }
+  // Runtime policy change from the desktop host; it persists the policy itself.
+  if (requestUrl.pathname === '/api/local-fallback-policy') {
+    if (req.method === 'POST') {
+      let update;
+      try {
+        update = JSON.parse((await readBody(req))?.toString() || '');
+      } catch {
+        return json({ error: 'expected { mode, allowlist, remoteBase }' }, 400);
+      }
+      if (!FALLBACK_MODES.has(update?.mode)) {
+        return json({ error: `mode must be one of ${[...FALLBACK_MODES].join(', ')}` }, 400);
+      }
+      if (update.remoteBase !== undefined) {
+        let remote;
+        try { remote = new URL(String(update.remoteBase)); } catch { remote = null; }
+        if (!remote || !['http:', 'https:'].includes(remote.protocol)) {
+          return json({ error: 'remoteBase must be an http(s) URL' }, 400);
+        }
+        context.remoteBase = String(update.remoteBase).replace(/\/$/, '');
+      }
+      context.fallbackPolicy = parseFallbackPolicy(update.mode, update.allowlist ?? []);
+      // Re-evaluate routes that were switched to the cloud under the old policy.
+      cloudPreferred.clear();
+      context.logger.log(`[local-api] cloud fallback policy: ${context.fallbackPolicy.mode} (remoteBase=${context.remoteBase})`);
+    }
+    return json({ ...context.fallbackPolicy, remoteBase: context.remoteBase });
+  }
+  if (requestUrl.pathname === '/api/local-routes') {
+    return json(await describeRoutes(routes, context, { probe: requestUrl.searchParams.get('probe') === '1' }));
+  }
   if (requestUrl.pathname === '/api/local-traffic-log') {
     if (req.method === 'DELETE') {
       trafficLog.length = 0;
@@ -967,6 +1333,18 @@ async function dispatch(requestUrl, req, routes, context) {
     }
     return json({ verboseMode });
   }
+  // Graceful stop requested by the desktop host. Used on Windows, where the
+  // host cannot deliver SIGTERM; the response is sent before draining starts.
+  if (requestUrl.pathname === '/api/local-shutdown') {
+    if (req.method !== 'POST') {
+      return json({ error: 'POST required' }, 405);
+    }
+    if (!context.onShutdownRequest) {
+      return json({ error: 'Shutdown not supported in this mode' }, 501);
+    }
+    setImmediate(() => context.onShutdownRequest('shutdown requested by host'));
+    return json({ shuttingDown: true });
+  }
   // Registration — call Convex directly (desktop frontend bypasses sidecar for this endpoint;
   // this handler only runs when CONVEX_URL is available, e.g. self-hosted deployments)
   if (requestUrl.pathname === '/api/register-interest' && req.method === 'POST') {
@@ -1092,17 +1470,16 @@ async function dispatch(requestUrl, req, routes, context) {
     }
   }
 
-  if (context.cloudFallback && cloudPreferred.has(requestUrl.pathname)) {
-    const cloudResponse = await tryCloudFallback(requestUrl, req, context);
+  if (cloudPreferred.has(requestUrl.pathname)) {
+    const cloudResponse = await tryCloudFallback(requestUrl, req, routes, context);
     if (cloudResponse) return cloudResponse;
   }
 
   const modulePath = pickModule(requestUrl.pathname, routes);
   if (!modulePath || !existsSync(modulePath)) {
-    if (context.cloudFallback) {
-      const cloudResponse = await tryCloudFallback(requestUrl, req, context, 'handler missing');
-      if (cloudResponse) return cloudResponse;
-    }
+    if (missingHandlers.size < MISSING_HANDLERS_MAX) missingHandlers.add(requestUrl.pathname);
+    const cloudResponse = await tryCloudFallback(requestUrl, req, routes, context, 'handler missing');
+    if (cloudResponse) return cloudResponse;
     logOnce(context.logger, requestUrl.pathname, 'no local handler');
     return json({ error: 'No local handler for this endpoint', endpoint: requestUrl.pathname }, 404);
   }
@@ -1111,10 +1488,8 @@ async function dispatch(requestUrl, req, routes, context) {
     const mod = await importHandler(modulePath);
     if (typeof mod.default !== 'function') {
       logOnce(context.logger, requestUrl.pathname, 'invalid handler module');
-      if (context.cloudFallback) {
-        const cloudResponse = await tryCloudFallback(requestUrl, req, context, `invalid handler module`);
-        if (cloudResponse) return cloudResponse;
-      }
+      const cloudResponse = await tryCloudFallback(requestUrl, req, routes, context, 'invalid handler module');
+      if (cloudResponse) return cloudResponse;
       return json({ error: 'Invalid handler module', endpoint: requestUrl.pathname }, 500);
     }
 
@@ -1125,18 +1500,17 @@ async function dispatch(requestUrl, req, routes, context) {
       body,
     });
 
+    req[REQUEST_UPSTREAM] = 'local';
     const response = await mod.default(request);
     if (!(response instanceof Response)) {
       logOnce(context.logger, requestUrl.pathname, 'handler returned non-Response');
-      if (context.cloudFallback) {
-        const cloudResponse = await tryCloudFallback(requestUrl, req, context, 'handler returned non-Response');
-        if (cloudResponse) return cloudResponse;
-      }
+      const cloudResponse = await tryCloudFallback(requestUrl, req, routes, context, 'handler returned non-Response');
+      if (cloudResponse) return cloudResponse;
       return json({ error: 'Handler returned invalid response', endpoint: requestUrl.pathname }, 500);
     }
 
-    if (!response.ok && context.cloudFallback) {
-      const cloudResponse = await tryCloudFallback(requestUrl, req, context, `local status ${response.status}`);
+    if (!response.ok) {
+      const cloudResponse = await tryCloudFallback(requestUrl, req, routes, context, `local status ${response.status}`);
       if (cloudResponse) { cloudPreferred.add(requestUrl.pathname); return cloudResponse; }
     }
 
@@ -1144,10 +1518,8 @@ async function dispatch(requestUrl, req, routes, context) {
   } catch (error) {
     const reason = error.code === 'ERR_MODULE_NOT_FOUND' ? 'missing dependency' : error.message;
     context.logger.error(`[local-api] ${requestUrl.pathname} → ${reason}`);
-    if (context.cloudFallback) {
-      const cloudResponse = await tryCloudFallback(requestUrl, req, context, error);
-      if (cloudResponse) { cloudPreferred.add(requestUrl.pathname); return cloudResponse; }
-    }
+    const cloudResponse = await tryCloudFallback(requestUrl, req, routes, context, error);
+    if (cloudResponse) { cloudPreferred.add(requestUrl.pathname); return cloudResponse; }
     return json({ error: 'Local handler error', reason, endpoint: requestUrl.pathname }, 502);
   }
 }
@@ -1169,7 +1541,12 @@ export async function createLocalApiServer(options = {}) {
     const start = Date.now();
     const skipRecord = req.method === 'OPTIONS'
       || requestUrl.pathname === '/api/local-traffic-log'
+      || requestUrl.pathname === '/api/local-status'
+      || requestUrl.pathname === '/api/service-status'
+      || requestUrl.pathname === '/api/local-routes'
+      || requestUrl.pathname === '/api/local-fallback-policy'
       || requestUrl.pathname === '/api/local-debug-toggle'
+      || requestUrl.pathname === '/api/local-shutdown'
       || requestUrl.pathname === '/api/local-env-update'
       || requestUrl.pathname === '/api/local-validate-secret';
 
@@ -1192,6 +1569,10 @@ export async function createLocalApiServer(options = {}) {
         });
       }
 
+      if (context.recordDir && !skipRecord) {
+        await recordFixture(context, req, requestUrl, response.status, headers, body);
+      }
+
       const acceptEncoding = req.headers['accept-encoding'] || '';
       body = await maybeCompressResponseBody(body, headers, acceptEncoding);
 
@@ -1201,6 +1582,7 @@ export async function createLocalApiServer(options = {}) {
 
       res.writeHead(response.status, headers);
       res.end(body);
+      writeMetric(req, requestUrl, routes, { status: response.status, startedAt: start, bytes: body.length, responseHeaders: headers });
     } catch (error) {
       const durationMs = Date.now() - start;
       context.logger.error('[local-api] fatal', error);
@@ -1216,8 +1598,11 @@ export async function createLocalApiServer(options = {}) {
         });
       }
 
-      res.writeHead(500, { 'content-type': 'application/json', ...makeCorsHeaders(req) });
-      res.end(JSON.stringify({ error: 'Internal server error' }));
+      const errorBody = JSON.stringify({ error: 'Internal server error' });
+      const errorHeaders = { 'content-type': 'application/json', ...makeCorsHeaders(req) };
+      res.writeHead(500, errorHeaders);
+      res.end(errorBody);
+      writeMetric(req, requestUrl, routes, { status: 500, startedAt: start, bytes: Buffer.byteLength(errorBody), responseHeaders: errorHeaders });
     }
   });
 
@@ -1226,6 +1611,20 @@ export async function createLocalApiServer(options = {}) {
     routes,
     server,
     async start() {
+      if (context.socketPath) {
+        await new Promise((resolve, reject) => {
+          server.once('error', reject);
+          server.listen(context.socketPath, () => { server.off('error', reject); resolve(); });
+        });
+        if (process.platform !== 'win32') {
+          // The host already made the parent dir 0700; this covers direct launches.
+          await chmod(context.socketPath, 0o600);
+        }
+        writeHandshake({ socket: context.socketPath, pid: process.pid });
+        context.logger.log(`[local-api] listening on socket ${context.socketPath} (apiDir=${context.apiDir}, routes=${routes.length}, cloudFallback=${context.fallbackPolicy.mode})`);
+        return { socket: context.socketPath };
+      }
+
       const tryListen = (port) => new Promise((resolve, reject) => {
         const onListening = () => { server.off('error', onError); resolve(); };
         const onError = (error) => { server.off('listening', onListening); reject(error); };
@@ -1237,7 +1636,7 @@ export async function createLocalApiServer(options = {}) {
       try {
         await tryListen(context.port);
       } catch (err) {
-        if (err?.code === 'EADDRINUSE') {
+        if (err?.code === 'EADDRINUSE' && !context.strictPort) {
           context.logger.log(`[local-api] port ${context.port} busy, falling back to OS-assigned port`);
           await tryListen(0);
         } else {
@@ -1249,17 +1648,16 @@ export async function createLocalApiServer(options = {}) {
       const boundPort = typeof address === 'object' && address?.port ? address.port : context.port;
       context.port = boundPort;
 
-      const portFile = process.env.LOCAL_API_PORT_FILE;
-      if (portFile) {
-        try { writeFileSync(portFile, String(boundPort)); } catch {}
-      }
+      writeHandshake({ port: boundPort, pid: process.pid });
 
-      context.logger.log(`[local-api] listening on http://127.0.0.1:${boundPort} (apiDir=${context.apiDir}, routes=${routes.length}, cloudFallback=${context.cloudFallback})`);
+      context.logger.log(`[local-api] listening on http://127.0.0.1:${boundPort} (apiDir=${context.apiDir}, routes=${routes.length}, cloudFallback=${context.fallbackPolicy.mode})`);
       return { port: boundPort };
     },
     async close() {
       await new Promise((resolve, reject) => {
         server.close((error) => (error ? reject(error) : resolve()));
+        // Stop waiting on idle keep-alive sockets; in-flight requests still finish.
+        server.closeIdleConnections?.();
       });
     },
   };
@@ -1267,10 +1665,29 @@ export async function createLocalApiServer(options = {}) {
 
 if (isMainModule()) {
   try {
-    const app = await createLocalApiServer();
+    let app = null;
+    let shuttingDown = false;
+    const shutdown = async (reason) => {
+      if (shuttingDown || !app) return;
+      shuttingDown = true;
+      app.context.logger.log(`[local-api] ${reason}, draining in-flight requests`);
+      try {
+        await app.close();
+      } catch (error) {
+        app.context.logger.error('[local-api] close failed during shutdown', error);
+      }
+      process.exit(0);
+    };
+    app = await createLocalApiServer({ onShutdownRequest: shutdown });
+    process.on('SIGTERM', () => { void shutdown('SIGTERM received'); });
     await app.start();
   } catch (error) {
     console.error('[local-api] startup failed', error);
+    writeHandshake({
+      error: error?.code || 'STARTUP_FAILED',
+      message: error instanceof Error ? error.message : String(error),
+      port: Number(process.env.LOCAL_API_PORT) || null,
+    });
     process.exit(1);
   }
 }
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::config::DesktopConfigState;
+use crate::local_api_capture;
+use crate::local_api_metrics::RequestSample;
+use crate::local_api_transport::{self, LocalApiAddr};
+use crate::logging::{iso_timestamp, now_ms};
+use crate::{require_trusted_window, LocalApiState};
+
+pub const DEFAULT_REMOTE_BASE: &str = "https://worldmonitor.app";
+const POLICY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackMode {
+    /// Failed local requests fail; nothing is sent to the remote.
+    #[default]
+    Never,
+    /// Any failed local request is retried against the remote.
+    Always,
+    /// Only paths or route patterns on the allowlist may fall back.
+    Allowlist,
+}
+
+impl FallbackMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            FallbackMode::Never => "never",
+            FallbackMode::Always => "always",
+            FallbackMode::Allowlist => "allowlist",
+        }
+    }
+}
+
+/// When the sidecar may hand a failed local request to the remote API.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudFallbackConfig {
+    pub mode: FallbackMode,
+    /// Used in `allowlist` mode. Entries are exact paths (`/api/fred-data`),
+    /// prefixes ending in `*` (`/api/news/*`) or route patterns
+    /// (`/api/[domain]/v1/[rpc]`).
+    pub allowlist: Vec<String>,
+    /// Where fallback requests go.
+    pub remote_base: String,
+}
+
+impl Default for CloudFallbackConfig {
+    fn default() -> Self {
+        CloudFallbackConfig {
+            mode: FallbackMode::Never,
+            allowlist: Vec::new(),
+            remote_base: DEFAULT_REMOTE_BASE.to_string(),
+        }
+    }
+}
+
+impl CloudFallbackConfig {
+    fn validate(&self) -> Result<(), String> {
+        let url = Url::parse(&self.remote_base).map_err(|e| format!("Invalid remote base URL: {e}"))?;
+        let loopback = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        if url.scheme() != "https" && !(url.scheme() == "http" && loopback) {
+            return Err("Remote base URL must use https (http is only allowed for loopback)".to_string());
+        }
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err("Remote base URL must not contain credentials".to_string());
+        }
+        if let Some(entry) = self.allowlist.iter().find(|e| !e.starts_with("/api/") || e.contains(',')) {
+            return Err(format!("Allowlist entry must be an /api/ path without commas: {entry}"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackOutcome {
+    /// The remote answered in place of the local handler.
+    Served,
+    /// The policy kept the request local.
+    Blocked,
+    /// Fallback was allowed but the remote could not be reached.
+    Failed,
+}
+
+/// Fallback details attached to a sidecar metric line.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct FallbackSample {
+    pub outcome: FallbackOutcome,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteFallbackStats {
+    pub served: u64,
+    pub blocked: u64,
+    pub failed: u64,
+    pub last_outcome: Option<FallbackOutcome>,
+    pub last_reason: String,
+    pub last_at: String,
+}
+
+/// Per-route fallback counts.
+#[derive(Default)]
+pub struct CloudFallbackStats {
+    routes: Mutex<BTreeMap<String, RouteFallbackStats>>,
+}
+
+impl CloudFallbackStats {
+    fn record(&self, route: &str, fallback: &FallbackSample, at: String) {
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = routes.entry(route.to_string()).or_default();
+        match fallback.outcome {
+            FallbackOutcome::Served => stats.served += 1,
+            FallbackOutcome::Blocked => stats.blocked += 1,
+            FallbackOutcome::Failed => stats.failed += 1,
+        }
+        stats.last_outcome = Some(fallback.outcome);
+        stats.last_reason = fallback.reason.clone();
+        stats.last_at = at;
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudFallbackReport {
+    pub policy: CloudFallbackConfig,
+    pub served: u64,
+    pub blocked: u64,
+    pub failed: u64,
+    /// Keyed by route pattern, as in the local API metrics.
+    pub routes: BTreeMap<String, RouteFallbackStats>,
+}
+
+fn config(app: &AppHandle) -> CloudFallbackConfig {
+    app.try_state::<DesktopConfigState>()
+        .map(|c| c.snapshot().cloud_fallback)
+        .unwrap_or_default()
+}
+
+/// Pass the policy to a sidecar about to be spawned.
+pub fn apply_env(app: &AppHandle, cmd: &mut Command) {
+    let config = config(app);
+    cmd.env("LOCAL_API_CLOUD_FALLBACK", config.mode.as_str())
+        .env("LOCAL_API_CLOUD_FALLBACK_ALLOWLIST", config.allowlist.join(","))
+        .env("LOCAL_API_REMOTE_BASE", &config.remote_base);
+}
+
+/// Called for every request the sidecar reports.
+pub fn observe(app: &AppHandle, sample: &RequestSample) {
+    let (Some(fallback), Some(stats)) = (&sample.fallback, app.try_state::<CloudFallbackStats>()) else {
+        return;
+    };
+    stats.record(&sample.route, fallback, iso_timestamp(now_ms()));
+}
+
+/// Push the policy to the running sidecar. Nothing to do when it is not
+/// running; the next spawn reads it from the environment.
+fn push_to_sidecar(app: &AppHandle, config: &CloudFallbackConfig) -> Result<(), String> {
+    if local_api_capture::is_replaying(app) {
+        return Ok(());
+    }
+    let state = app.state::<LocalApiState>();
+    let running = state.child.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+    let Some(addr) = LocalApiAddr::current(&state).filter(|_| running) else {
+        return Ok(());
+    };
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    if let Some(token) = state.token.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        headers.push(("Authorization".to_string(), format!("Bearer {token}")));
+    }
+    let body = json!({
+        "mode": config.mode,
+        "allowlist": config.allowlist,
+        "remoteBase": config.remote_base,
+    })
+    .to_string();
+    let response = local_api_transport::send(
+        &addr,
+        "POST",
+        "/api/local-fallback-policy",
+        &headers,
+        body.as_bytes(),
+        POLICY_TIMEOUT,
+    )
+    .map_err(|e| format!("fallback policy request failed: {e}"))?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!(
+            "fallback policy HTTP {}: {}",
+            response.status,
+            String::from_utf8_lossy(&response.body)
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_cloud_fallback_report(
+    webview: Webview,
+    app: AppHandle,
+    stats: tauri::State<'_, CloudFallbackStats>,
+) -> Result<CloudFallbackReport, String> {
+    require_trusted_window(webview.label())?;
+    let routes = stats.routes.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    Ok(CloudFallbackReport {
+        policy: config(&app),
+        served: routes.values().map(|s| s.served).sum(),
+        blocked: routes.values().map(|s| s.blocked).sum(),
+        failed: routes.values().map(|s| s.failed).sum(),
+        routes,
+    })
+}
+
+/// Persist the policy and apply it to the running sidecar without a restart.
+#[tauri::command]
+pub async fn set_cloud_fallback_policy(
+    webview: Webview,
+    app: AppHandle,
+    policy: CloudFallbackConfig,
+) -> Result<CloudFallbackConfig, String> {
+    require_trusted_window(webview.label())?;
+    let mut policy = policy;
+    policy.remote_base = policy.remote_base.trim().trim_end_matches('/').to_string();
+    policy.allowlist = policy
+        .allowlist
+        .iter()
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect();
+    policy.validate()?;
+    app.state::<DesktopConfigState>()
+        .update(&app, |c| c.cloud_fallback = policy.clone())?;
+    log_event!(
+        &app,
+        Warn,
+        "cloud fallback policy changed",
+        mode = policy.mode,
+        remote_base = policy.remote_base.clone(),
+        allowlist = policy.allowlist.clone(),
+        window = webview.label()
+    );
+
+    let applied = policy.clone();
+    tauri::async_runtime::spawn_blocking(move || push_to_sidecar(&app, &applied))
+        .await
+        .map_err(|e| format!("Fallback policy task failed: {e}"))?
+        .map_err(|e| format!("Policy saved, but the running local API did not accept it: {e}"))?;
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_remote_base_and_allowlist() {
+        let mut policy = CloudFallbackConfig::default();
+        assert!(policy.validate().is_ok());
+
+        policy.remote_base = "http://127.0.0.1:8787".into();
+        assert!(policy.validate().is_ok());
+        policy.remote_base = "http://mirror.example.com".into();
+        assert!(policy.validate().is_err());
+        policy.remote_base = "https://user:pw@mirror.example.com".into();
+        assert!(policy.validate().is_err());
+
+        policy.remote_base = "https://mirror.example.com".into();
+        policy.allowlist = vec!["/api/news/*".into(), "/api/[domain]/v1/[rpc]".into()];
+        assert!(policy.validate().is_ok());
+        policy.allowlist.push("news/*".into());
+        assert!(policy.validate().is_err());
+    }
+
+    #[test]
+    fn counts_fallbacks_per_route() {
+        let stats = CloudFallbackStats::default();
+        let served = FallbackSample { outcome: FallbackOutcome::Served, reason: "local status 500".into() };
+        let blocked = FallbackSample { outcome: FallbackOutcome::Blocked, reason: "handler missing".into() };
+        stats.record("/api/fred-data", &served, "t1".into());
+        stats.record("/api/fred-data", &served, "t2".into());
+        stats.record("/api/fred-data", &blocked, "t3".into());
+
+        let routes = stats.routes.lock().unwrap();
+        let fred = &routes["/api/fred-data"];
+        assert_eq!((fred.served, fred.blocked, fred.failed), (2, 1, 0));
+        assert_eq!(fred.last_outcome, Some(FallbackOutcome::Blocked));
+        assert_eq!(fred.last_reason, "handler missing");
+        assert_eq!(fred.last_at, "t3");
+    }
+
+    #[test]
+    fn reads_policy_from_older_config_files() {
+        let config: CloudFallbackConfig = serde_json::from_str(r#"{"mode":"allowlist"}"#).unwrap();
+        assert_eq!(config.mode, FallbackMode::Allowlist);
+        assert_eq!(config.remote_base, DEFAULT_REMOTE_BASE);
+    }
+}
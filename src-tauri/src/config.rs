@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::cloud_fallback::CloudFallbackConfig;
+use crate::egress_proxy::EgressConfig;
+use crate::host_bridge::HostBridgeConfig;
+use crate::js_runtime::JsRuntime;
+use crate::local_api_metrics::MetricsConfig;
+use crate::log_rotation::LogRotationPolicy;
+use crate::logging::LoggingConfig;
+use crate::resource_limits::ResourceLimits;
+use crate::retention::RetentionPolicy;
+
+const DESKTOP_CONFIG_FILE: &str = "desktop-config.json";
+
+/// Host-side settings that are not secrets. Stored as plain JSON next to
+/// persistent-cache.json; unknown or missing sections fall back to defaults so
+/// older files keep loading after new sections are added.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopConfig {
+    pub retention: RetentionPolicy,
+    pub local_api: LocalApiConfig,
+    pub log_rotation: LogRotationPolicy,
+    pub logging: LoggingConfig,
+    pub egress: EgressConfig,
+    pub host_bridge: HostBridgeConfig,
+    pub metrics: MetricsConfig,
+    pub cloud_fallback: CloudFallbackConfig,
+}
+
+/// Sidecar process lifecycle settings.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalApiConfig {
+    /// Pin the sidecar to this loopback port. Startup fails with a clear error
+    /// if it is taken; when unset the default port is preferred and the OS
+    /// picks a free one if it is busy.
+    pub port: Option<u16>,
+    /// How long `stop_local_api` waits for in-flight handlers after asking the
+    /// sidecar to exit before it force-kills the process.
+    pub shutdown_grace_ms: u64,
+    /// `socket` serves the API on a Unix domain socket (a named pipe on
+    /// Windows) reached through the `wmapi` URI scheme, so nothing listens
+    /// on TCP. `port` is ignored in that mode.
+    pub transport: LocalApiTransport,
+    /// JavaScript runtime for the sidecar: `node`, `bun` or `deno`.
+    /// `LOCAL_API_RUNTIME` overrides it.
+    pub runtime: JsRuntime,
+    /// Heap, address-space and CPU caps applied at spawn.
+    pub limits: ResourceLimits,
+    /// Confine the sidecar with Landlock on Linux: read-only access to the
+    /// resource dir and the runtime, writes only to the data/log and socket
+    /// dirs. Ignored elsewhere.
+    pub sandbox: bool,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocalApiTransport {
+    #[default]
+    Tcp,
+    Socket,
+}
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        LocalApiConfig {
+            port: None,
+            shutdown_grace_ms: 3000,
+            transport: LocalApiTransport::Tcp,
+            runtime: JsRuntime::Node,
+            limits: ResourceLimits::default(),
+            sandbox: false,
+        }
+    }
+}
+
+pub struct DesktopConfigState {
+    config: Mutex<DesktopConfig>,
+}
+
+impl DesktopConfigState {
+    pub fn load(path: &Path) -> Self {
+        let config = fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<DesktopConfig>(&s).ok())
+            .unwrap_or_default();
+        DesktopConfigState {
+            config: Mutex::new(config),
+        }
+    }
+
+    pub fn snapshot(&self) -> DesktopConfig {
+        self.config.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Apply `change` to a copy, persist it, then commit to memory so a failed
+    /// write never leaves the in-memory config ahead of the file.
+    pub fn update(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut DesktopConfig),
+    ) -> Result<DesktopConfig, String> {
+        let mut config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+        let mut proposed = config.clone();
+        change(&mut proposed);
+        let path = config_file_path(app)?;
+        let serialized = serde_json::to_string_pretty(&proposed)
+            .map_err(|e| format!("Failed to serialize desktop config: {e}"))?;
+        fs::write(&path, serialized)
+            .map_err(|e| format!("Failed to write desktop config {}: {e}", path.display()))?;
+        *config = proposed.clone();
+        Ok(proposed)
+    }
+}
+
+pub fn config_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory {}: {e}", dir.display()))?;
+    Ok(dir.join(DESKTOP_CONFIG_FILE))
+}
@@ -0,0 +1,256 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Webview};
+
+use crate::logging::{iso_timestamp, now_ms, utc_date_time};
+use crate::redact::{redact_log, try_redact_log};
+use crate::{logs_dir_path, open_path_in_shell, require_trusted_window};
+
+const PENDING_SUFFIX: &str = ".pending.json";
+const SEEN_SUFFIX: &str = ".json";
+
+/// Set once setup knows the log dir; panics before that land in the temp dir
+/// and are moved over on the next launch.
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Serialize, Deserialize)]
+pub struct CrashReport {
+    pub timestamp: String,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+impl CrashReport {
+    /// Mask secrets in the free-text fields; panic messages often carry
+    /// request URLs and values read from the environment.
+    fn redacted(mut self, redact: impl Fn(&str) -> String) -> Self {
+        self.message = redact(&self.message);
+        self.backtrace = redact(&self.backtrace);
+        self
+    }
+}
+
+#[derive(Serialize)]
+pub struct PreviousCrash {
+    /// File name of the report; pass back to `open_crash_report` / `dismiss_previous_crash`.
+    pub id: String,
+    pub path: String,
+    pub report: CrashReport,
+}
+
+fn fallback_dir() -> PathBuf {
+    std::env::temp_dir().join("world-monitor-crashes")
+}
+
+fn crash_dir() -> PathBuf {
+    CRASH_DIR.get().cloned().unwrap_or_else(fallback_dir)
+}
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Write a crash report for every panic, then defer to the default hook so
+/// stderr output is unchanged. Installed first thing in `main`, before any
+/// thread can panic. The hook never waits on a lock, so it cannot deadlock on
+/// state the panicking thread holds; secrets are masked before the write.
+pub fn install_panic_hook(version: &'static str) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let now = now_ms();
+        let report = CrashReport {
+            timestamp: iso_timestamp(now),
+            version: version.to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: std::thread::current().name().unwrap_or("<unnamed>").to_string(),
+            message: payload_message(info.payload()),
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: Backtrace::force_capture().to_string(),
+        }
+        .redacted(try_redact_log);
+        let (year, month, day, hour, minute, second) = utc_date_time(now / 1000);
+        let name = format!(
+            "crash-{year:04}{month:02}{day:02}-{hour:02}{minute:02}{second:02}-{}{PENDING_SUFFIX}",
+            std::process::id()
+        );
+        let dir = crash_dir();
+        if fs::create_dir_all(&dir).is_ok() {
+            if let Ok(json) = serde_json::to_string_pretty(&report) {
+                let _ = fs::write(dir.join(name), json);
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+/// Point the hook at the log dir and adopt reports written before it existed.
+pub fn init(app: &AppHandle) {
+    let Ok(dir) = logs_dir_path(app) else {
+        return;
+    };
+    if let Ok(entries) = fs::read_dir(fallback_dir()) {
+        for entry in entries.flatten() {
+            let _ = fs::rename(entry.path(), dir.join(entry.file_name()));
+        }
+    }
+    let _ = CRASH_DIR.set(dir);
+}
+
+fn pending_reports(dir: &Path) -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("crash-") && n.ends_with(PENDING_SUFFIX))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Names embed a sortable UTC timestamp.
+    reports.sort();
+    reports
+}
+
+/// Resolve a report id from the UI to a file in the crash dir. Rejects
+/// anything that is not a bare crash report file name.
+fn report_path(id: &str) -> Result<PathBuf, String> {
+    let valid = id.starts_with("crash-")
+        && id.ends_with(SEEN_SUFFIX)
+        && !id.contains(['/', '\\'])
+        && !id.contains("..");
+    if !valid {
+        return Err(format!("Invalid crash report id '{id}'"));
+    }
+    Ok(crash_dir().join(id))
+}
+
+/// The newest crash report the user has not dismissed yet, if any.
+#[tauri::command]
+pub fn get_previous_crash(webview: Webview) -> Result<Option<PreviousCrash>, String> {
+    require_trusted_window(webview.label())?;
+    let Some(path) = pending_reports(&crash_dir()).pop() else {
+        return Ok(None);
+    };
+    let json = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read crash report {}: {e}", path.display()))?;
+    let report: CrashReport =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid crash report {}: {e}", path.display()))?;
+    Ok(Some(PreviousCrash {
+        id: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: path.display().to_string(),
+        report: report.redacted(redact_log),
+    }))
+}
+
+#[tauri::command]
+pub fn open_crash_report(webview: Webview, id: String) -> Result<String, String> {
+    require_trusted_window(webview.label())?;
+    let path = report_path(&id)?;
+    if !path.is_file() {
+        return Err(format!("Crash report {id} not found"));
+    }
+    open_path_in_shell(&path)?;
+    Ok(path.display().to_string())
+}
+
+fn mark_seen(path: &Path) -> Result<(), String> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let seen = name.replace(PENDING_SUFFIX, SEEN_SUFFIX);
+    fs::rename(path, path.with_file_name(seen))
+        .map_err(|e| format!("Failed to update crash report {}: {e}", path.display()))
+}
+
+/// Called on a clean exit. A panic in a worker thread unwinds that thread
+/// only, so the app kept running and there is nothing to report on the next
+/// launch; its report stays in the log dir.
+pub fn settle_clean_exit() {
+    let own = format!("-{}{PENDING_SUFFIX}", std::process::id());
+    for path in pending_reports(&crash_dir()) {
+        if path.to_string_lossy().ends_with(&own) {
+            let _ = mark_seen(&path);
+        }
+    }
+}
+
+/// Mark every pending report as seen. Reports stay in the log dir (and in
+/// diagnostics bundles) until retention removes them.
+#[tauri::command]
+pub fn dismiss_previous_crash(webview: Webview) -> Result<(), String> {
+    require_trusted_window(webview.label())?;
+    for path in pending_reports(&crash_dir()) {
+        mark_seen(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_ids_must_be_bare_crash_files() {
+        assert!(report_path("crash-20260101-000000-42.pending.json").is_ok());
+        assert!(report_path("crash-20260101-000000-42.json").is_ok());
+        assert!(report_path("../desktop-config.json").is_err());
+        assert!(report_path("crash-../../etc/passwd.json").is_err());
+        assert!(report_path("desktop.log").is_err());
+    }
+
+    #[test]
+    fn reports_mask_secrets_in_message_and_backtrace() {
+        let report = CrashReport {
+            timestamp: String::new(),
+            version: String::new(),
+            os: String::new(),
+            arch: String::new(),
+            thread: "main".to_string(),
+            message: "fetch https://api.example.com/v1?api_key=abcdef123456 failed".to_string(),
+            location: None,
+            backtrace: "Authorization: Bearer abcdefghijklmnop".to_string(),
+        }
+        .redacted(try_redact_log);
+        assert!(!report.message.contains("abcdef123456"));
+        assert!(!report.backtrace.contains("abcdefghijklmnop"));
+    }
+
+    #[test]
+    fn reports_mask_stored_secret_values() {
+        const STORED: &str = "wm-stored-secret-8c1f2e";
+        crate::redact::store(crate::redact::Redactor::new(vec![STORED.to_string()]));
+        let report = CrashReport {
+            timestamp: String::new(),
+            version: String::new(),
+            os: String::new(),
+            arch: String::new(),
+            thread: "main".to_string(),
+            message: format!("called `Result::unwrap()` on an `Err` value: upstream rejected {STORED}"),
+            location: None,
+            backtrace: String::new(),
+        }
+        .redacted(try_redact_log);
+        assert!(!report.message.contains(STORED), "{}", report.message);
+    }
+}
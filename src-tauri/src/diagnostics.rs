@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Webview};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+use crate::logging::{iso_timestamp, now_ms, utc_date_time};
+use crate::js_runtime::{runtime_report, selected_runtime};
+use crate::redact::Redactor;
+use crate::{
+    desktop_runtime_info, local_api_paths, logs_dir_path, require_trusted_window,
+    LocalApiState, PersistentCache, SecretsCache, DESKTOP_JSON_LOG_FILE, DESKTOP_LOG_FILE,
+    LOCAL_API_LOG_FILE, SUPPORTED_SECRET_KEYS,
+};
+
+/// Rendering workarounds `main` may set before WebKitGTK starts, plus the
+/// inputs it keys them on.
+const LINUX_RENDERING_ENV_VARS: &[&str] = &[
+    "WEBKIT_DISABLE_DMABUF_RENDERER",
+    "WEBKIT_DISABLE_COMPOSITING_MODE",
+    "LIBGL_ALWAYS_SOFTWARE",
+    "__NV_DISABLE_EXPLICIT_SYNC",
+    "GDK_BACKEND",
+    "GIO_MODULE_DIR",
+    "GIO_USE_VFS",
+    "WEBKIT_DISABLE_SANDBOX_THIS_IS_DANGEROUS",
+    "WEBKIT_FORCE_SANDBOX",
+    "WAYLAND_DISPLAY",
+    "APPIMAGE",
+];
+/// Only the newest part of each log goes in the bundle.
+const LOG_TAIL_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_CRASH_REPORTS: usize = 5;
+
+#[derive(Serialize)]
+struct CacheEntrySize {
+    key: String,
+    bytes: usize,
+}
+
+fn collect_report(app: &AppHandle) -> Value {
+    let runtime = runtime_report(app, selected_runtime(app));
+    let (script, resource_root) = local_api_paths(app);
+
+    let rendering_env: BTreeMap<&str, Option<String>> = LINUX_RENDERING_ENV_VARS
+        .iter()
+        .map(|name| (*name, std::env::var(name).ok()))
+        .collect();
+
+    let secrets_present = {
+        let cache = app.state::<SecretsCache>();
+        let secrets = cache.secrets.lock().unwrap_or_else(|e| e.into_inner());
+        secrets_present(&secrets)
+    };
+
+    let mut cache_entries: Vec<CacheEntrySize> = app
+        .try_state::<PersistentCache>()
+        .map(|cache| {
+            let data = cache.data.lock().unwrap_or_else(|e| e.into_inner());
+            data.iter()
+                .map(|(key, value)| CacheEntrySize {
+                    key: key.clone(),
+                    bytes: serde_json::to_string(value).map(|s| s.len()).unwrap_or(0),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    cache_entries.sort_by_key(|e| std::cmp::Reverse(e.bytes));
+
+    json!({
+        "generated_at": iso_timestamp(now_ms()),
+        "app_version": app.package_info().version.to_string(),
+        "runtime": desktop_runtime_info(&app.state::<LocalApiState>()),
+        "js_runtime": runtime,
+        "sidecar": {
+            "script": script.display().to_string(),
+            "script_exists": script.exists(),
+            "resource_root": resource_root.display().to_string(),
+        },
+        "rendering_env": rendering_env,
+        "secrets_present": secrets_present,
+        "cache": {
+            "entries": cache_entries.len(),
+            "total_bytes": cache_entries.iter().map(|e| e.bytes).sum::<usize>(),
+            "keys": cache_entries,
+        },
+    })
+}
+
+/// Which supported secrets are set. Only the flag goes in the report, never the value.
+fn secrets_present(secrets: &HashMap<String, String>) -> BTreeMap<&'static str, bool> {
+    SUPPORTED_SECRET_KEYS
+        .iter()
+        .map(|key| (*key, secrets.get(*key).is_some_and(|v| !v.trim().is_empty())))
+        .collect()
+}
+
+fn read_tail(path: &Path, max_bytes: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(max_bytes)))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Deflated entries stamped with the bundle's creation time (UTC).
+fn entry_options(unix_secs: u64) -> SimpleFileOptions {
+    let (year, month, day, hour, minute, second) = utc_date_time(unix_secs);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    match DateTime::from_date_and_time(year as u16, month as u8, day as u8, hour as u8, minute as u8, second as u8) {
+        Ok(modified) => options.last_modified_time(modified),
+        Err(_) => options,
+    }
+}
+
+fn add_entry<W: Write + Seek>(zip: &mut ZipWriter<W>, options: SimpleFileOptions, name: &str, data: &[u8]) -> Result<(), String> {
+    zip.start_file(name, options)
+        .and_then(|()| zip.write_all(data).map_err(Into::into))
+        .map_err(|e| format!("Failed to add {name} to diagnostics bundle: {e}"))
+}
+
+fn bundle_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match app.path().download_dir() {
+        Ok(dir) if dir.is_dir() => Ok(dir),
+        _ => logs_dir_path(app),
+    }
+}
+
+/// Zip the report, the log tails and the newest crash reports. Every entry
+/// passes through the redactor, so secret values never leave the machine even
+/// if a log or a cached response happened to contain one.
+fn build_bundle(redactor: &Redactor, report: &Value, logs_dir: &Path, now: u64) -> Result<Vec<u8>, String> {
+    // Built in memory so a failure never leaves a truncated bundle behind.
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = entry_options(now / 1000);
+
+    let report = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize diagnostics: {e}"))?;
+    add_entry(&mut zip, options, "diagnostics.json", redactor.redact(&report).as_bytes())?;
+
+    for base in [DESKTOP_LOG_FILE, DESKTOP_JSON_LOG_FILE, LOCAL_API_LOG_FILE] {
+        for name in [base.to_string(), format!("{base}.1")] {
+            let Ok(bytes) = read_tail(&logs_dir.join(&name), LOG_TAIL_BYTES) else {
+                continue;
+            };
+            let text = redactor.redact(&String::from_utf8_lossy(&bytes));
+            add_entry(&mut zip, options, &format!("logs/{name}"), text.as_bytes())?;
+        }
+    }
+
+    let mut crash_reports: Vec<PathBuf> = fs::read_dir(logs_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("crash-")))
+                .collect()
+        })
+        .unwrap_or_default();
+    crash_reports.sort();
+    for path in crash_reports.iter().rev().take(MAX_CRASH_REPORTS) {
+        let (Ok(text), Some(name)) = (fs::read_to_string(path), path.file_name()) else {
+            continue;
+        };
+        let entry = format!("crashes/{}", name.to_string_lossy());
+        add_entry(&mut zip, options, &entry, redactor.redact(&text).as_bytes())?;
+    }
+
+    Ok(zip
+        .finish()
+        .map_err(|e| format!("Failed to finish diagnostics bundle: {e}"))?
+        .into_inner())
+}
+
+/// Write the bundle and return its path.
+pub fn write_diagnostics_bundle(app: &AppHandle) -> Result<PathBuf, String> {
+    let now = now_ms();
+    let bytes = build_bundle(&Redactor::from_app(app), &collect_report(app), &logs_dir_path(app)?, now)?;
+    let (year, month, day, hour, minute, second) = utc_date_time(now / 1000);
+    let path = bundle_dir(app)?.join(format!(
+        "world-monitor-diagnostics-{year:04}{month:02}{day:02}-{hour:02}{minute:02}{second:02}.zip"
+    ));
+    fs::write(&path, bytes)
+        .map_err(|e| format!("Failed to write diagnostics bundle {}: {e}", path.display()))?;
+    log_event!(app, Info, "diagnostics bundle written", path = path.display().to_string());
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn export_diagnostics_bundle(webview: Webview, app: AppHandle) -> Result<String, String> {
+    require_trusted_window(webview.label())?;
+    tauri::async_runtime::spawn_blocking(move || write_diagnostics_bundle(&app))
+        .await
+        .map_err(|e| format!("Diagnostics task failed: {e}"))?
+        .map(|path| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_redacts_secrets_in_every_entry() {
+        const SECRET: &str = "gsk_live_9f8e7d6c5b4a";
+        const QUERY_KEY: &str = "q7w8e9r0t1y2";
+        let dir = std::env::temp_dir().join(format!("wm-diagnostics-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let url = format!("https://api.example.com/v1/feed?api_key={QUERY_KEY}");
+        fs::write(
+            dir.join(LOCAL_API_LOG_FILE),
+            format!("[local-api] upstream GET {url} failed; retrying with {SECRET}\n"),
+        )
+        .unwrap();
+        fs::write(dir.join("crash-1.json"), format!("{{\"message\":\"panicked fetching {url}\"}}")).unwrap();
+
+        let secrets = HashMap::from([("GROQ_API_KEY".to_string(), SECRET.to_string())]);
+        let report = json!({
+            "secrets_present": secrets_present(&secrets),
+            "config": { "feed_url": url, "groq_key": SECRET },
+        });
+        let bytes = build_bundle(&Redactor::new(secrets.values().cloned()), &report, &dir, 1_790_000_000_000).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut names = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut text = String::new();
+            entry.read_to_string(&mut text).unwrap();
+            assert!(!text.contains(SECRET), "{} leaks the secret: {text}", entry.name());
+            assert!(!text.contains(QUERY_KEY), "{} leaks the query key: {text}", entry.name());
+            names.push(entry.name().to_string());
+        }
+        assert!(names.contains(&"diagnostics.json".to_string()));
+        assert!(names.contains(&format!("logs/{LOCAL_API_LOG_FILE}")));
+        assert!(names.contains(&"crashes/crash-1.json".to_string()));
+
+        let present = &report["secrets_present"];
+        assert_eq!(present["GROQ_API_KEY"], json!(true));
+        assert_eq!(present["FRED_API_KEY"], json!(false));
+        assert!(present.as_object().unwrap().values().all(Value::is_boolean));
+    }
+}
@@ -0,0 +1,243 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::local_api_capture;
+use crate::local_api_transport::{self, LocalApiAddr};
+use crate::logging::{self, now_ms, Level};
+use crate::{require_trusted_window, LocalApiState};
+
+pub const HEALTH_EVENT: &str = "local-api-status";
+const STARTING_POLL: Duration = Duration::from_millis(250);
+const READY_POLL: Duration = Duration::from_secs(5);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long a fresh sidecar may take to answer before we report it down.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(20);
+/// Consecutive failed probes of a previously ready sidecar before it is down.
+const DOWN_AFTER_FAILURES: u32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthState {
+    Starting,
+    Ready,
+    Degraded,
+    Down,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LocalApiStatus {
+    pub state: HealthState,
+    pub port: Option<u16>,
+    pub pid: Option<u32>,
+    pub last_error: Option<String>,
+    /// Unix millis of the last state change.
+    pub since: u64,
+}
+
+pub struct HealthMonitor {
+    status: Mutex<LocalApiStatus>,
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        HealthMonitor {
+            status: Mutex::new(LocalApiStatus {
+                state: HealthState::Starting,
+                port: None,
+                pid: None,
+                last_error: None,
+                since: now_ms(),
+            }),
+        }
+    }
+}
+
+impl HealthMonitor {
+    pub fn snapshot(&self) -> LocalApiStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Record a probe result. Only state changes are logged and emitted; the
+/// port/pid/error fields are refreshed silently on every call.
+pub fn report(app: &AppHandle, state: HealthState, error: Option<String>) {
+    let Some(monitor) = app.try_state::<HealthMonitor>() else {
+        return;
+    };
+    let (pid, port) = {
+        let local = app.state::<LocalApiState>();
+        let pid = local.child.lock().ok().and_then(|g| g.as_ref().map(|c| c.id()));
+        let port = local.port.lock().ok().and_then(|g| *g);
+        (pid, port)
+    };
+
+    let changed = {
+        let mut status = monitor.status.lock().unwrap_or_else(|e| e.into_inner());
+        let changed = status.state != state;
+        status.port = port;
+        status.pid = pid;
+        if error.is_some() || state == HealthState::Ready {
+            status.last_error = error;
+        }
+        if changed {
+            status.state = state;
+            status.since = now_ms();
+        }
+        changed.then(|| status.clone())
+    };
+
+    if let Some(snapshot) = changed {
+        let level = match state {
+            HealthState::Down => Level::Error,
+            HealthState::Degraded => Level::Warn,
+            _ => Level::Info,
+        };
+        logging::log(
+            app,
+            level,
+            module_path!(),
+            "local API status changed",
+            &[
+                ("state", json!(state)),
+                ("pid", json!(snapshot.pid)),
+                ("port", json!(snapshot.port)),
+                ("error", json!(snapshot.last_error)),
+            ],
+        );
+        let _ = app.emit(HEALTH_EVENT, &snapshot);
+    }
+}
+
+fn probe(addr: &LocalApiAddr, token: Option<&str>) -> Result<(), String> {
+    let headers: Vec<(String, String)> = token
+        .map(|t| vec![("Authorization".to_string(), format!("Bearer {t}"))])
+        .unwrap_or_default();
+    let response = local_api_transport::send(addr, "GET", "/api/local-status", &headers, &[], PROBE_TIMEOUT)
+        .map_err(|e| format!("probe failed: {e}"))?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("local-status HTTP {}", response.status));
+    }
+    let body: serde_json::Value = serde_json::from_slice(&response.body)
+        .map_err(|e| format!("local-status invalid JSON: {e}"))?;
+    if body.get("success").and_then(|v| v.as_bool()) != Some(true) {
+        return Err("local-status did not report success".to_string());
+    }
+    Ok(())
+}
+
+/// State after a probe. `failures` counts consecutive failed probes since the
+/// child was last ready; before that, failures only matter once the startup
+/// window has passed.
+fn next_state(probe_ok: bool, was_ready: bool, failures: u32, since_spawn: Duration) -> HealthState {
+    if probe_ok {
+        HealthState::Ready
+    } else if !was_ready {
+        if since_spawn >= STARTUP_TIMEOUT {
+            HealthState::Down
+        } else {
+            HealthState::Starting
+        }
+    } else if failures >= DOWN_AFTER_FAILURES {
+        HealthState::Down
+    } else {
+        HealthState::Degraded
+    }
+}
+
+/// Poll the sidecar's authenticated `/api/local-status` so the UI learns when
+/// it is usable without `setup` blocking on it. Polls fast until the first
+/// successful answer for each child, then settles to a slower heartbeat.
+pub fn spawn_health_monitor(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut watched_pid: Option<u32> = None;
+        let mut spawned_at = Instant::now();
+        let mut was_ready = false;
+        let mut failures = 0u32;
+
+        loop {
+            if local_api_capture::is_replaying(&app) {
+                // The replay server reports its own readiness; there is no child to watch.
+                watched_pid = None;
+                std::thread::sleep(STARTING_POLL);
+                continue;
+            }
+            let state = app.state::<LocalApiState>();
+            let pid = state.child.lock().ok().and_then(|g| g.as_ref().map(|c| c.id()));
+            let Some(pid) = pid else {
+                // Start-up failures and crashes report their own error; keep it.
+                if watched_pid.take().is_some() {
+                    report(&app, HealthState::Down, Some("sidecar process not running".to_string()));
+                }
+                std::thread::sleep(STARTING_POLL);
+                continue;
+            };
+
+            if watched_pid != Some(pid) {
+                watched_pid = Some(pid);
+                spawned_at = Instant::now();
+                was_ready = false;
+                failures = 0;
+                report(&app, HealthState::Starting, None);
+            }
+
+            let addr = LocalApiAddr::current(&state);
+            let token = state.token.lock().ok().and_then(|g| g.clone());
+
+            let result = match addr {
+                Some(addr) => probe(&addr, token.as_deref()),
+                None => Err("sidecar has not reported its address yet".to_string()),
+            };
+
+            let error = result.err();
+            if error.is_none() {
+                was_ready = true;
+                failures = 0;
+            } else if was_ready {
+                failures += 1;
+            }
+            match next_state(error.is_none(), was_ready, failures, spawned_at.elapsed()) {
+                // Still within the startup window; keep waiting.
+                HealthState::Starting => {}
+                HealthState::Down if !was_ready => report(
+                    &app,
+                    HealthState::Down,
+                    error.map(|err| format!("not ready after {}s: {err}", STARTUP_TIMEOUT.as_secs())),
+                ),
+                next => report(&app, next, error),
+            }
+
+            std::thread::sleep(if was_ready && failures == 0 { READY_POLL } else { STARTING_POLL });
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_local_api_status(
+    webview: Webview,
+    monitor: tauri::State<'_, HealthMonitor>,
+) -> Result<LocalApiStatus, String> {
+    require_trusted_window(webview.label())?;
+    Ok(monitor.snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_results_map_to_states() {
+        let early = Duration::from_secs(1);
+        assert_eq!(next_state(false, false, 0, early), HealthState::Starting);
+        assert_eq!(next_state(false, false, 0, STARTUP_TIMEOUT), HealthState::Down);
+        assert_eq!(next_state(true, true, 0, early), HealthState::Ready);
+        assert_eq!(next_state(false, true, 1, early), HealthState::Degraded);
+        assert_eq!(next_state(false, true, DOWN_AFTER_FAILURES - 1, early), HealthState::Degraded);
+        assert_eq!(next_state(false, true, DOWN_AFTER_FAILURES, early), HealthState::Down);
+        // A successful probe recovers from any state.
+        assert_eq!(next_state(true, true, 0, STARTUP_TIMEOUT * 2), HealthState::Ready);
+    }
+}
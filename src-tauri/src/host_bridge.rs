@@ -0,0 +1,301 @@
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::config::DesktopConfigState;
+use crate::loopback_http::{self, HttpRequest};
+use crate::{generate_local_token, PersistentCache, SecretsCache, SUPPORTED_SECRET_KEYS};
+
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_NOTIFICATION_TITLE_CHARS: usize = 120;
+const MAX_NOTIFICATION_BODY_CHARS: usize = 1000;
+
+/// Which host capabilities the sidecar may call back into.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostBridgeConfig {
+    pub enabled: bool,
+    /// Capability names: `secrets.get`, `notify`, `cache.read`. Anything
+    /// not listed is refused with 403. Empty by default; users opt in per
+    /// capability.
+    pub allow: Vec<String>,
+}
+
+impl Default for HostBridgeConfig {
+    fn default() -> Self {
+        HostBridgeConfig {
+            enabled: true,
+            allow: Vec::new(),
+        }
+    }
+}
+
+/// A request from the sidecar, as `{"method": ..., "params": {...}}`.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "method", content = "params")]
+enum HostCall {
+    /// A keychain secret, including ones saved after the sidecar started.
+    #[serde(rename = "secrets.get")]
+    SecretGet { key: String },
+    /// A native OS notification.
+    #[serde(rename = "notify")]
+    Notify {
+        title: String,
+        #[serde(default)]
+        body: String,
+    },
+    /// An entry from persistent-cache.json.
+    #[serde(rename = "cache.read")]
+    CacheRead { key: String },
+}
+
+impl HostCall {
+    fn capability(&self) -> &'static str {
+        match self {
+            HostCall::SecretGet { .. } => "secrets.get",
+            HostCall::Notify { .. } => "notify",
+            HostCall::CacheRead { .. } => "cache.read",
+        }
+    }
+}
+
+/// Loopback endpoint the sidecar uses to call back into the host.
+#[derive(Default)]
+pub struct HostBridge {
+    port: Mutex<Option<u16>>,
+    token: Mutex<Option<String>>,
+}
+
+impl HostBridge {
+    pub fn token(&self) -> Option<String> {
+        self.token.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+fn config(app: &AppHandle) -> HostBridgeConfig {
+    app.try_state::<DesktopConfigState>()
+        .map(|c| c.snapshot().host_bridge)
+        .unwrap_or_default()
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &Value) {
+    loopback_http::respond(stream, status, &[("Content-Type", "application/json")], body.to_string().as_bytes());
+}
+
+fn error_body(message: impl Into<String>) -> Value {
+    json!({ "error": message.into() })
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+/// Run an allowed call. `Err` carries the HTTP status and message.
+fn execute(app: &AppHandle, call: HostCall) -> Result<Value, (u16, String)> {
+    match call {
+        HostCall::SecretGet { key } => {
+            if !SUPPORTED_SECRET_KEYS.contains(&key.as_str()) {
+                return Err((400, format!("unsupported secret key: {key}")));
+            }
+            let cache = app.state::<SecretsCache>();
+            let secrets = cache.secrets.lock().unwrap_or_else(|e| e.into_inner());
+            Ok(json!({ "value": secrets.get(&key) }))
+        }
+        HostCall::Notify { title, body } => {
+            let title = truncate_chars(title.trim(), MAX_NOTIFICATION_TITLE_CHARS);
+            if title.is_empty() {
+                return Err((400, "notification title is empty".to_string()));
+            }
+            app.notification()
+                .builder()
+                .title(title)
+                .body(truncate_chars(&body, MAX_NOTIFICATION_BODY_CHARS))
+                .show()
+                .map_err(|e| (500, format!("failed to show notification: {e}")))?;
+            Ok(json!({ "shown": true }))
+        }
+        HostCall::CacheRead { key } => {
+            let cache = app.state::<PersistentCache>();
+            Ok(json!({ "value": cache.get(&key) }))
+        }
+    }
+}
+
+/// Check the token, route and allowlist. `Err` carries the status and
+/// message to answer with.
+fn authorize(request: &HttpRequest, expected_auth: &str, allow: &[String]) -> Result<HostCall, (u16, String)> {
+    if request.header("authorization") != Some(expected_auth) {
+        return Err((401, "Unauthorized".to_string()));
+    }
+    if request.path != "/call" {
+        return Err((404, "Not found".to_string()));
+    }
+    if request.method != "POST" {
+        return Err((405, "POST required".to_string()));
+    }
+    let call: HostCall =
+        serde_json::from_slice(&request.body).map_err(|e| (400, format!("invalid host call: {e}")))?;
+    let capability = call.capability();
+    if !allow.iter().any(|c| c == capability) {
+        return Err((403, format!("capability not allowed: {capability}")));
+    }
+    Ok(call)
+}
+
+fn handle_connection(app: &AppHandle, mut stream: TcpStream, expected_auth: &str) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let request = match loopback_http::read_request(&mut stream, MAX_BODY_BYTES) {
+        Ok(request) => request,
+        Err((status, reason)) => {
+            respond(&mut stream, status, &error_body(&reason));
+            log_event!(app, Warn, "rejected host bridge request", status = status, reason = reason);
+            return;
+        }
+    };
+    // Read the allowlist per call so config changes apply without a restart.
+    let call = match authorize(&request, expected_auth, &config(app).allow) {
+        Ok(call) => call,
+        Err((status, message)) => {
+            respond(&mut stream, status, &error_body(&message));
+            match status {
+                401 => log_event!(app, Warn, "host bridge request without valid token", path = request.path),
+                403 => log_event!(app, Warn, "refused host bridge call not on allowlist", reason = message),
+                _ => {}
+            }
+            return;
+        }
+    };
+    let capability = call.capability();
+    match execute(app, call) {
+        Ok(result) => {
+            respond(&mut stream, 200, &json!({ "result": result }));
+            log_event!(app, Debug, "host bridge call", capability = capability);
+        }
+        Err((status, message)) => {
+            respond(&mut stream, status, &error_body(&message));
+            log_event!(app, Warn, "host bridge call failed", capability = capability, error = message);
+        }
+    }
+}
+
+/// Start the callback endpoint unless `host_bridge.enabled` is false. Runs
+/// before the sidecar is spawned so its address can be passed at spawn.
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    if !config(app).enabled {
+        return Ok(());
+    }
+    let bridge = app.state::<HostBridge>();
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(|e| format!("Failed to bind host bridge: {e}"))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read host bridge address: {e}"))?
+        .port();
+    let token = generate_local_token();
+    let expected_auth = format!("Bearer {token}");
+    *bridge.token.lock().unwrap_or_else(|e| e.into_inner()) = Some(token);
+    *bridge.port.lock().unwrap_or_else(|e| e.into_inner()) = Some(port);
+    crate::redact::refresh(app);
+
+    log_event!(app, Info, "host bridge listening", port = port);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let app = app.clone();
+            let expected_auth = expected_auth.clone();
+            std::thread::spawn(move || handle_connection(&app, stream, &expected_auth));
+        }
+    });
+    Ok(())
+}
+
+/// Tell the sidecar where the bridge is and which capabilities it may use.
+/// The allowlist is only a hint there; the bridge enforces it on every call.
+pub fn apply_env(app: &AppHandle, cmd: &mut Command) {
+    let bridge = app.state::<HostBridge>();
+    let port = *bridge.port.lock().unwrap_or_else(|e| e.into_inner());
+    let config = config(app);
+    match (config.enabled, port, bridge.token()) {
+        (true, Some(port), Some(token)) => {
+            cmd.env("LOCAL_API_HOST_URL", format!("http://127.0.0.1:{port}"))
+                .env("LOCAL_API_HOST_TOKEN", token);
+        }
+        _ => {
+            cmd.env_remove("LOCAL_API_HOST_URL")
+                .env_remove("LOCAL_API_HOST_TOKEN");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_typed_host_calls() {
+        let call: HostCall =
+            serde_json::from_str(r#"{"method":"secrets.get","params":{"key":"FRED_API_KEY"}}"#).unwrap();
+        assert_eq!(call, HostCall::SecretGet { key: "FRED_API_KEY".into() });
+        assert_eq!(call.capability(), "secrets.get");
+
+        let call: HostCall = serde_json::from_str(r#"{"method":"notify","params":{"title":"Alert"}}"#).unwrap();
+        assert_eq!(
+            call,
+            HostCall::Notify {
+                title: "Alert".into(),
+                body: String::new()
+            }
+        );
+
+        assert!(serde_json::from_str::<HostCall>(r#"{"method":"fs.read","params":{"path":"/etc"}}"#).is_err());
+        assert!(serde_json::from_str::<HostCall>(r#"{"method":"cache.read","params":{}}"#).is_err());
+    }
+
+    fn request(method: &str, path: &str, authorization: Option<&str>, body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.into(),
+            path: path.into(),
+            headers: authorization.map(|a| ("Authorization".into(), a.into())).into_iter().collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn authorizes_token_route_and_allowlist() {
+        let auth = "Bearer host-token";
+        let allow = vec!["secrets.get".to_string()];
+        let secret = r#"{"method":"secrets.get","params":{"key":"FRED_API_KEY"}}"#;
+        let status = |req: &HttpRequest, allow: &[String]| authorize(req, auth, allow).map_err(|(s, _)| s);
+
+        assert_eq!(
+            status(&request("POST", "/call", Some(auth), secret), &allow),
+            Ok(HostCall::SecretGet { key: "FRED_API_KEY".into() })
+        );
+        assert_eq!(status(&request("POST", "/call", None, secret), &allow), Err(401));
+        assert_eq!(status(&request("POST", "/call", Some("Bearer wrong"), secret), &allow), Err(401));
+        // The token is checked before anything else is revealed.
+        assert_eq!(status(&request("GET", "/nope", Some("Bearer wrong"), ""), &allow), Err(401));
+        assert_eq!(status(&request("POST", "/other", Some(auth), secret), &allow), Err(404));
+        assert_eq!(status(&request("GET", "/call", Some(auth), ""), &allow), Err(405));
+        assert_eq!(status(&request("POST", "/call", Some(auth), "{}"), &allow), Err(400));
+        assert_eq!(
+            status(&request("POST", "/call", Some(auth), r#"{"method":"notify","params":{"title":"Hi"}}"#), &allow),
+            Err(403)
+        );
+        // Nothing is allowed until the user opts in.
+        let defaults = HostBridgeConfig::default();
+        assert!(defaults.allow.is_empty());
+        assert_eq!(status(&request("POST", "/call", Some(auth), secret), &defaults.allow), Err(403));
+    }
+}
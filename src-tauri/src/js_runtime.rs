@@ -0,0 +1,439 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::config::DesktopConfigState;
+use crate::{require_trusted_window, LocalApiState};
+
+/// Overrides `local_api.runtime` from desktop-config.json.
+const RUNTIME_ENV: &str = "LOCAL_API_RUNTIME";
+/// A broken shim must not stall sidecar startup.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// JavaScript runtime that runs the local API sidecar. Each one differs in
+/// where it is installed, how it reports its version, and how it is invoked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsRuntime {
+    #[default]
+    Node,
+    Bun,
+    Deno,
+}
+
+impl JsRuntime {
+    pub fn parse(value: &str) -> Option<JsRuntime> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "node" | "nodejs" => Some(JsRuntime::Node),
+            "bun" => Some(JsRuntime::Bun),
+            "deno" => Some(JsRuntime::Deno),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            JsRuntime::Node => "Node.js",
+            JsRuntime::Bun => "Bun",
+            JsRuntime::Deno => "Deno",
+        }
+    }
+
+    fn binary_name(self) -> &'static str {
+        match (self, cfg!(windows)) {
+            (JsRuntime::Node, false) => "node",
+            (JsRuntime::Node, true) => "node.exe",
+            (JsRuntime::Bun, false) => "bun",
+            (JsRuntime::Bun, true) => "bun.exe",
+            (JsRuntime::Deno, false) => "deno",
+            (JsRuntime::Deno, true) => "deno.exe",
+        }
+    }
+
+    /// Env var naming an explicit executable for this runtime.
+    pub fn bin_env(self) -> &'static str {
+        match self {
+            JsRuntime::Node => "LOCAL_API_NODE_BIN",
+            JsRuntime::Bun => "LOCAL_API_BUN_BIN",
+            JsRuntime::Deno => "LOCAL_API_DENO_BIN",
+        }
+    }
+
+    /// Oldest major the sidecar runs on: Node 18 for global `fetch`, Bun 1
+    /// and Deno 2 for working `node:` built-ins.
+    pub fn min_major(self) -> u32 {
+        match self {
+            JsRuntime::Node => 18,
+            JsRuntime::Bun => 1,
+            JsRuntime::Deno => 2,
+        }
+    }
+
+    fn common_locations(self) -> Vec<PathBuf> {
+        let home = env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from);
+        let mut locations = match (self, cfg!(windows)) {
+            (JsRuntime::Node, true) => vec![
+                PathBuf::from(r"C:\Program Files\nodejs\node.exe"),
+                PathBuf::from(r"C:\Program Files (x86)\nodejs\node.exe"),
+            ],
+            (_, true) => Vec::new(),
+            (_, false) => ["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin", "/opt/local/bin"]
+                .iter()
+                .map(|dir| Path::new(dir).join(self.binary_name()))
+                .collect(),
+        };
+        // Bun and Deno install per user by default.
+        let user_dir = match self {
+            JsRuntime::Node => None,
+            JsRuntime::Bun => Some(".bun"),
+            JsRuntime::Deno => Some(".deno"),
+        };
+        if let (Some(home), Some(dir)) = (home, user_dir) {
+            locations.push(home.join(dir).join("bin").join(self.binary_name()));
+        }
+        locations
+    }
+
+    /// Major version from `--version` output: `v20.11.1` (Node), `1.1.20`
+    /// (Bun), `deno 2.0.6 (stable, ...)` followed by more lines (Deno).
+    fn parse_major(self, output: &str) -> Option<u32> {
+        let first = output.lines().next()?.trim();
+        let version = match self {
+            JsRuntime::Node => first.strip_prefix('v')?,
+            JsRuntime::Bun => first,
+            JsRuntime::Deno => first.strip_prefix("deno ")?,
+        };
+        version.split('.').next()?.parse().ok()
+    }
+
+    /// Arguments before and including the script. Deno gets only the
+    /// permissions the sidecar needs: writes are limited to `write_dirs`.
+    pub fn script_args(self, script: &str, write_dirs: &[String], max_heap_mb: Option<u64>) -> Vec<String> {
+        let heap = max_heap_mb.map(|mb| format!("--max-old-space-size={mb}"));
+        match self {
+            JsRuntime::Node => heap.into_iter().chain([script.to_string()]).collect(),
+            // Bun's heap cap goes through the environment; see `apply_env`.
+            JsRuntime::Bun => vec![script.to_string()],
+            JsRuntime::Deno => {
+                let mut args = vec![
+                    "run".to_string(),
+                    "--no-prompt".to_string(),
+                    "--allow-env".to_string(),
+                    "--allow-net".to_string(),
+                    "--allow-sys".to_string(),
+                    "--allow-read".to_string(),
+                    format!("--allow-write={}", write_dirs.join(",")),
+                ];
+                args.extend(heap.map(|flag| format!("--v8-flags={flag}")));
+                args.push(script.to_string());
+                args
+            }
+        }
+    }
+
+    /// Whether `max_heap_mb` is enforced. Bun only takes it as a GC sizing
+    /// hint, so its out-of-memory exits are not heap-limit breaches.
+    pub fn enforces_heap_cap(self) -> bool {
+        self != JsRuntime::Bun
+    }
+
+    /// Runtime-specific environment for the sidecar process.
+    pub fn apply_env(self, cmd: &mut Command, max_heap_mb: Option<u64>) {
+        match self {
+            JsRuntime::Node => {}
+            // Keep Bun from writing a transpiler cache into the user's home.
+            JsRuntime::Bun => {
+                cmd.env("BUN_RUNTIME_TRANSPILER_CACHE_PATH", "0");
+                if let Some(mb) = max_heap_mb {
+                    cmd.env("BUN_JSC_forceRAMSize", mb.saturating_mul(1024 * 1024).to_string());
+                }
+            }
+            JsRuntime::Deno => {
+                cmd.env("DENO_NO_UPDATE_CHECK", "1").env("DENO_NO_PROMPT", "1");
+            }
+        }
+    }
+}
+
+/// `LOCAL_API_RUNTIME` if set and valid, else `local_api.runtime`.
+pub fn selected_runtime(app: &AppHandle) -> JsRuntime {
+    if let Ok(value) = env::var(RUNTIME_ENV) {
+        match JsRuntime::parse(&value) {
+            Some(runtime) => return runtime,
+            None => log_event!(app, Warn, "ignoring unknown LOCAL_API_RUNTIME", value = value),
+        }
+    }
+    app.try_state::<DesktopConfigState>()
+        .map(|c| c.snapshot().local_api.runtime)
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CandidateSource {
+    /// `LOCAL_API_<RUNTIME>_BIN`
+    Env,
+    /// The Node shipped in release bundles.
+    Bundled,
+    Path,
+    CommonLocation,
+}
+
+#[derive(Clone, Serialize)]
+pub struct RuntimeCandidate {
+    pub path: String,
+    pub source: CandidateSource,
+    /// First line of `--version` output.
+    pub version: Option<String>,
+    pub error: Option<String>,
+    pub supported: bool,
+}
+
+#[derive(Serialize)]
+pub struct RuntimeReport {
+    pub runtime: JsRuntime,
+    pub min_major: u32,
+    pub candidates: Vec<RuntimeCandidate>,
+    /// First supported candidate; what the next start would use.
+    pub chosen: Option<String>,
+    /// Executable the running sidecar was spawned with, when it runs on this
+    /// runtime.
+    pub spawned: Option<String>,
+}
+
+/// Every executable we could use for `runtime`, in preference order, without duplicates.
+fn candidate_paths(app: &AppHandle, runtime: JsRuntime) -> Vec<(PathBuf, CandidateSource)> {
+    let mut found: Vec<(PathBuf, CandidateSource)> = Vec::new();
+
+    if let Ok(explicit) = env::var(runtime.bin_env()) {
+        let explicit_path = PathBuf::from(explicit);
+        if explicit_path.is_file() {
+            found.push((explicit_path, CandidateSource::Env));
+        } else {
+            log_event!(
+                app,
+                Warn,
+                format!("{} is set but not a valid file", runtime.bin_env()),
+                path = explicit_path.display().to_string()
+            );
+        }
+    }
+
+    if runtime == JsRuntime::Node && !cfg!(debug_assertions) {
+        if let Ok(resource_dir) = app.path().resource_dir() {
+            let bundled = resource_dir.join("sidecar").join("node").join(runtime.binary_name());
+            if bundled.is_file() {
+                found.push((bundled, CandidateSource::Bundled));
+            }
+        }
+    }
+
+    if let Some(path_var) = env::var_os("PATH") {
+        for dir in env::split_paths(&path_var) {
+            let candidate = dir.join(runtime.binary_name());
+            if candidate.is_file() {
+                found.push((candidate, CandidateSource::Path));
+            }
+        }
+    }
+
+    found.extend(
+        runtime
+            .common_locations()
+            .into_iter()
+            .filter(|path| path.is_file())
+            .map(|path| (path, CandidateSource::CommonLocation)),
+    );
+
+    // PATH often lists the same install twice (or a symlink to a common location).
+    let mut seen = Vec::new();
+    found.retain(|(path, _)| {
+        let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if seen.contains(&key) {
+            false
+        } else {
+            seen.push(key);
+            true
+        }
+    });
+    found
+}
+
+/// Run `<binary> --version`, killing it if it does not answer in time.
+fn probe_version(binary: &Path) -> Result<String, String> {
+    let mut cmd = Command::new(binary);
+    cmd.arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let mut child = cmd.spawn().map_err(|e| format!("failed to run: {e}"))?;
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("no answer within {}s", PROBE_TIMEOUT.as_secs()));
+            }
+            Err(e) => return Err(format!("failed to wait: {e}")),
+        }
+    }
+    let output = child.wait_with_output().map_err(|e| format!("failed to read output: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn probe(runtime: JsRuntime, path: PathBuf, source: CandidateSource) -> RuntimeCandidate {
+    let min = runtime.min_major();
+    let (version, error, supported) = match probe_version(&path) {
+        Ok(output) => {
+            let version = output.lines().next().unwrap_or_default().to_string();
+            match runtime.parse_major(&output) {
+                Some(major) if major >= min => (Some(version), None, true),
+                Some(_) => (
+                    Some(version),
+                    Some(format!("older than {} {min}", runtime.display_name())),
+                    false,
+                ),
+                None => (Some(version), Some("unrecognized version output".to_string()), false),
+            }
+        }
+        Err(e) => (None, Some(e), false),
+    };
+    RuntimeCandidate {
+        path: path.display().to_string(),
+        source,
+        version,
+        error,
+        supported,
+    }
+}
+
+/// First candidate for `runtime` that runs and is new enough. Rejected
+/// candidates are logged and listed in the error when nothing qualifies.
+pub fn resolve_runtime_binary(app: &AppHandle, runtime: JsRuntime) -> Result<PathBuf, String> {
+    let name = runtime.display_name();
+    let min = runtime.min_major();
+    let mut rejected = Vec::new();
+    for (path, source) in candidate_paths(app, runtime) {
+        let candidate = probe(runtime, path.clone(), source);
+        if candidate.supported {
+            return Ok(path);
+        }
+        let reason = candidate.error.unwrap_or_default();
+        let version = candidate.version.unwrap_or_else(|| "unknown".to_string());
+        log_event!(
+            app,
+            Warn,
+            "skipping unusable runtime candidate",
+            runtime = runtime,
+            path = candidate.path,
+            version = version,
+            reason = reason
+        );
+        rejected.push(format!("{} ({version}: {reason})", candidate.path));
+    }
+    if rejected.is_empty() {
+        Err(format!(
+            "{name} executable not found. Install {name} {min}+ or set {}",
+            runtime.bin_env()
+        ))
+    } else {
+        Err(format!(
+            "No usable {name} found; {name} {min}+ is required. Rejected: {}. \
+             Install a newer {name} or set {}",
+            rejected.join(", "),
+            runtime.bin_env()
+        ))
+    }
+}
+
+/// The executable `start_local_api` spawned for the running sidecar, if it
+/// uses `runtime`.
+fn spawned_binary(app: &AppHandle, runtime: JsRuntime) -> Option<String> {
+    let state = app.try_state::<LocalApiState>()?;
+    if state.child.lock().unwrap_or_else(|e| e.into_inner()).is_none() {
+        return None;
+    }
+    let spawned = state.runtime.lock().unwrap_or_else(|e| e.into_inner());
+    spawned
+        .as_ref()
+        .filter(|(used, _)| *used == runtime)
+        .map(|(_, path)| path.display().to_string())
+}
+
+/// Probe every candidate, including those after the one we would pick.
+pub fn runtime_report(app: &AppHandle, runtime: JsRuntime) -> RuntimeReport {
+    let candidates: Vec<RuntimeCandidate> = candidate_paths(app, runtime)
+        .into_iter()
+        .map(|(path, source)| probe(runtime, path, source))
+        .collect();
+    let chosen = candidates.iter().find(|c| c.supported).map(|c| c.path.clone());
+    RuntimeReport {
+        runtime,
+        min_major: runtime.min_major(),
+        candidates,
+        chosen,
+        spawned: spawned_binary(app, runtime),
+    }
+}
+
+/// All executables found for `runtime` (default: the selected one), their
+/// versions, and which one the sidecar would use.
+#[tauri::command]
+pub async fn get_js_runtime_candidates(
+    webview: Webview,
+    app: AppHandle,
+    runtime: Option<JsRuntime>,
+) -> Result<RuntimeReport, String> {
+    require_trusted_window(webview.label())?;
+    let runtime = runtime.unwrap_or_else(|| selected_runtime(&app));
+    tauri::async_runtime::spawn_blocking(move || runtime_report(&app, runtime))
+        .await
+        .map_err(|e| format!("Runtime probe task failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_runtime_major_versions() {
+        assert_eq!(JsRuntime::Node.parse_major("v20.11.1\n"), Some(20));
+        assert_eq!(JsRuntime::Node.parse_major("v16.20.2"), Some(16));
+        assert_eq!(JsRuntime::Node.parse_major("20.11.1"), None);
+        assert_eq!(JsRuntime::Bun.parse_major("1.1.20\n"), Some(1));
+        assert_eq!(
+            JsRuntime::Deno.parse_major("deno 2.0.6 (stable, release, x86_64-unknown-linux-gnu)\nv8 12.9\ntypescript 5.6.2"),
+            Some(2)
+        );
+        assert_eq!(JsRuntime::Deno.parse_major("v20.11.1"), None);
+    }
+
+    #[test]
+    fn builds_runtime_arguments() {
+        let args = JsRuntime::Deno.script_args("server.mjs", &["/data".to_string(), "/run".to_string()], Some(512));
+        assert_eq!(args.first().map(String::as_str), Some("run"));
+        assert!(args.contains(&"--allow-write=/data,/run".to_string()));
+        assert!(args.contains(&"--v8-flags=--max-old-space-size=512".to_string()));
+        assert_eq!(args.last().map(String::as_str), Some("server.mjs"));
+        assert_eq!(JsRuntime::Node.script_args("server.mjs", &[], None), vec!["server.mjs"]);
+        assert_eq!(
+            JsRuntime::Node.script_args("server.mjs", &[], Some(512)),
+            vec!["--max-old-space-size=512", "server.mjs"]
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use base64::Engine;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::health::{self, HealthState};
+use crate::loopback_http;
+use crate::{generate_local_token, require_trusted_window, LocalApiState};
+
+/// Must match `FIXTURE_VERSION` in sidecar/local-api-server.mjs.
+const FIXTURE_VERSION: u32 = 1;
+const REPLAY_READ_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REPLAY_BODY_BYTES: usize = 64 * 1024;
+
+/// Whether sidecar traffic is recorded to, or served from, a fixture dir.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum CaptureMode {
+    #[default]
+    Off,
+    /// The sidecar saves every request/response pair into `dir`.
+    Record { dir: PathBuf },
+    /// The host answers `/api/*` from the fixtures in `dir`; no sidecar runs.
+    Replay { dir: PathBuf },
+}
+
+struct ReplayServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+pub struct LocalApiCapture {
+    mode: Mutex<CaptureMode>,
+    replay: Mutex<Option<ReplayServer>>,
+}
+
+impl LocalApiCapture {
+    pub fn new(mode: CaptureMode) -> Self {
+        LocalApiCapture {
+            mode: Mutex::new(mode),
+            replay: Mutex::new(None),
+        }
+    }
+
+    fn mode(&self) -> CaptureMode {
+        self.mode.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// `--record-local-api <dir>` or `--replay-local-api <dir>` (also `=<dir>`).
+pub fn mode_from_args(args: impl IntoIterator<Item = String>) -> Result<CaptureMode, String> {
+    let mut mode = CaptureMode::Off;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        if flag != "--record-local-api" && flag != "--replay-local-api" {
+            continue;
+        }
+        let dir = inline
+            .or_else(|| args.next())
+            .filter(|d| !d.is_empty() && !d.starts_with("--"))
+            .ok_or_else(|| format!("{flag} needs a fixture directory"))?;
+        if mode != CaptureMode::Off {
+            return Err("--record-local-api and --replay-local-api are mutually exclusive".to_string());
+        }
+        let dir = PathBuf::from(dir);
+        mode = if flag == "--record-local-api" {
+            CaptureMode::Record { dir }
+        } else {
+            CaptureMode::Replay { dir }
+        };
+    }
+    Ok(mode)
+}
+
+fn current_mode(app: &AppHandle) -> CaptureMode {
+    app.try_state::<LocalApiCapture>()
+        .map(|c| c.mode())
+        .unwrap_or_default()
+}
+
+pub fn record_dir(app: &AppHandle) -> Option<PathBuf> {
+    match current_mode(app) {
+        CaptureMode::Record { dir } => Some(dir),
+        _ => None,
+    }
+}
+
+pub fn replay_dir(app: &AppHandle) -> Option<PathBuf> {
+    match current_mode(app) {
+        CaptureMode::Replay { dir } => Some(dir),
+        _ => None,
+    }
+}
+
+pub fn is_replaying(app: &AppHandle) -> bool {
+    app.try_state::<LocalApiCapture>()
+        .is_some_and(|c| c.replay.lock().unwrap_or_else(|e| e.into_inner()).is_some())
+}
+
+fn fixtures_root(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?
+        .join("fixtures"))
+}
+
+/// A fixture dir named by the webview, resolved under `root`. The record
+/// dir becomes writable for the sandboxed sidecar, so the webview may only
+/// pick a plain relative path there.
+fn fixture_subdir(root: &Path, dir: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(dir.trim());
+    let plain = relative
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)));
+    if !plain {
+        return Err(format!("Fixture dir must be a relative path inside {}: {dir}", root.display()));
+    }
+    Ok(root.join(relative))
+}
+
+/// Switch modes from a window. Takes effect when the local API is next
+/// (re)started. `dir` is relative to `fixtures/` in app data; any directory
+/// can still be given with the command-line flags.
+pub fn set_mode(app: &AppHandle, mode: &str, dir: Option<String>) -> Result<CaptureMode, String> {
+    let root = fixtures_root(app)?;
+    let dir = match dir.filter(|d| !d.trim().is_empty()) {
+        Some(dir) => fixture_subdir(&root, &dir)?,
+        None => root.join("local-api"),
+    };
+    let mode = match mode {
+        "off" => CaptureMode::Off,
+        "record" => CaptureMode::Record { dir },
+        "replay" => CaptureMode::Replay { dir },
+        other => return Err(format!("Unknown capture mode: {other}")),
+    };
+    let capture = app.state::<LocalApiCapture>();
+    *capture.mode.lock().unwrap_or_else(|e| e.into_inner()) = mode.clone();
+    log_event!(app, Info, "local API capture mode changed", mode = &mode);
+    Ok(mode)
+}
+
+#[derive(Deserialize)]
+struct FixtureFile {
+    version: u32,
+    request: FixtureRequest,
+    response: FixtureResponse,
+}
+
+#[derive(Deserialize)]
+struct FixtureRequest {
+    method: String,
+    path: String,
+    #[serde(default)]
+    query: String,
+    body: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FixtureResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+struct Fixture {
+    request_body: Vec<u8>,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// `(method, path, canonical query)`.
+type FixtureKey = (String, String, String);
+
+/// Must match `canonicalQuery` in sidecar/local-api-server.mjs.
+fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<&str> = query.trim_start_matches('?').split('&').filter(|p| !p.is_empty()).collect();
+    pairs.sort_unstable();
+    pairs.join("&")
+}
+
+fn load_fixtures(dir: &Path) -> Result<HashMap<FixtureKey, Vec<Fixture>>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read fixture dir {}: {e}", dir.display()))?;
+    let decode = |s: &str| base64::engine::general_purpose::STANDARD.decode(s);
+    let mut fixtures: HashMap<FixtureKey, Vec<Fixture>> = HashMap::new();
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str::<FixtureFile>(&json).map_err(|e| e.to_string()))
+            .and_then(|file| {
+                if file.version != FIXTURE_VERSION {
+                    return Err(format!("unsupported fixture version {}", file.version));
+                }
+                let request_body = file.request.body.as_deref().map(decode).transpose().map_err(|e| e.to_string())?;
+                let body = decode(&file.response.body).map_err(|e| e.to_string())?;
+                Ok((file.request, file.response, request_body.unwrap_or_default(), body))
+            });
+        let (request, response, request_body, body) =
+            parsed.map_err(|e| format!("Invalid fixture {}: {e}", path.display()))?;
+        let key = (request.method.to_ascii_uppercase(), request.path, canonical_query(&request.query));
+        fixtures.entry(key).or_default().push(Fixture {
+            request_body,
+            status: response.status,
+            headers: response.headers,
+            body,
+        });
+    }
+    Ok(fixtures)
+}
+
+/// A fixture for this exact method, path, query and request body.
+fn find_fixture<'a>(
+    fixtures: &'a HashMap<FixtureKey, Vec<Fixture>>,
+    method: &str,
+    target: &str,
+    body: &[u8],
+) -> Option<&'a Fixture> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let key = (method.to_ascii_uppercase(), path.to_string(), canonical_query(query));
+    fixtures.get(&key)?.iter().find(|f| f.request_body == body)
+}
+
+/// Same origins the sidecar's CORS check accepts.
+fn cors_origin(origin: Option<&str>) -> &str {
+    static ALLOWED: OnceLock<Regex> = OnceLock::new();
+    let allowed = ALLOWED.get_or_init(|| {
+        Regex::new(
+            r"^(tauri://localhost|https?://(localhost|127\.0\.0\.1|tauri\.localhost)(:\d+)?|https://([a-z0-9-]+\.)?worldmonitor\.app)$",
+        )
+        .expect("valid origin pattern")
+    });
+    match origin {
+        Some(origin) if allowed.is_match(origin) => origin,
+        _ => "tauri://localhost",
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, origin: Option<&str>, headers: &[(&str, &str)], body: &[u8]) {
+    let mut all = vec![
+        ("Access-Control-Allow-Origin", cors_origin(origin)),
+        ("Access-Control-Allow-Methods", "GET, POST, PUT, DELETE, OPTIONS"),
+        ("Access-Control-Allow-Headers", "Content-Type, Authorization"),
+        ("Vary", "Origin"),
+    ];
+    all.extend_from_slice(headers);
+    loopback_http::respond(stream, status, &all, body);
+}
+
+fn write_json(stream: &mut TcpStream, status: u16, origin: Option<&str>, body: serde_json::Value) {
+    write_response(
+        stream,
+        status,
+        origin,
+        &[("Content-Type", "application/json"), ("X-Local-Api-Replay", "control")],
+        body.to_string().as_bytes(),
+    );
+}
+
+fn serve_replay(
+    app: &AppHandle,
+    mut stream: TcpStream,
+    fixtures: &HashMap<FixtureKey, Vec<Fixture>>,
+    port: u16,
+    expected_auth: &str,
+) {
+    let _ = stream.set_read_timeout(Some(REPLAY_READ_TIMEOUT));
+    let request = match loopback_http::read_request(&mut stream, MAX_REPLAY_BODY_BYTES) {
+        Ok(request) => request,
+        Err((status, reason)) => {
+            write_json(&mut stream, status, None, json!({ "error": reason }));
+            return;
+        }
+    };
+    let origin = request.header("origin");
+    let path = request.path.split('?').next().unwrap_or_default();
+    if request.method == "OPTIONS" {
+        write_response(&mut stream, 204, origin, &[("Access-Control-Max-Age", "86400")], b"");
+        return;
+    }
+    if path == "/api/service-status" {
+        let status = json!({
+            "success": true,
+            "summary": { "operational": 1, "degraded": 0, "outage": 0, "unknown": 0 },
+            "services": [{ "id": "local-api", "name": "Local Desktop API", "category": "dev", "status": "operational", "description": "Replaying recorded fixtures" }],
+            "local": { "enabled": true, "mode": "replay", "port": port },
+        });
+        write_json(&mut stream, 200, origin, status);
+        return;
+    }
+    if request.header("authorization") != Some(expected_auth) {
+        write_json(&mut stream, 401, origin, json!({ "error": "Unauthorized" }));
+        return;
+    }
+    if path == "/api/local-status" {
+        let fixture_count: usize = fixtures.values().map(Vec::len).sum();
+        write_json(
+            &mut stream,
+            200,
+            origin,
+            json!({ "success": true, "mode": "replay", "port": port, "fixtures": fixture_count }),
+        );
+        return;
+    }
+
+    match find_fixture(fixtures, &request.method, &request.path, &request.body) {
+        Some(fixture) => {
+            let mut headers: Vec<(&str, &str)> =
+                fixture.headers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            headers.push(("X-Local-Api-Replay", "hit"));
+            write_response(&mut stream, fixture.status, origin, &headers, &fixture.body);
+        }
+        None => {
+            log_event!(app, Warn, "no recorded fixture for replayed request", method = &request.method, path = path);
+            write_response(
+                &mut stream,
+                404,
+                origin,
+                &[("Content-Type", "application/json"), ("X-Local-Api-Replay", "miss")],
+                json!({ "error": "No recorded fixture for this request", "endpoint": path, "replay": true })
+                    .to_string()
+                    .as_bytes(),
+            );
+        }
+    }
+}
+
+/// Serve the fixtures on `port` in place of the sidecar.
+pub fn start_replay(app: &AppHandle, dir: &Path, port: u16) -> Result<(), String> {
+    let capture = app.state::<LocalApiCapture>();
+    if capture.replay.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+        return Ok(());
+    }
+    let fixtures = Arc::new(load_fixtures(dir)?);
+    let fixture_count: usize = fixtures.values().map(Vec::len).sum();
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .map_err(|e| format!("Failed to bind local API replay server: {e}"))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read replay server address: {e}"))?
+        .port();
+
+    let state = app.state::<LocalApiState>();
+    let token = {
+        let mut slot = state.token.lock().unwrap_or_else(|e| e.into_inner());
+        slot.get_or_insert_with(generate_local_token).clone()
+    };
+    crate::redact::refresh(app);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    *capture.replay.lock().unwrap_or_else(|e| e.into_inner()) = Some(ReplayServer {
+        port,
+        stop: stop.clone(),
+    });
+    *state.port.lock().unwrap_or_else(|e| e.into_inner()) = Some(port);
+
+    let expected_auth = format!("Bearer {token}");
+    let thread_app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let app = thread_app.clone();
+            let fixtures = fixtures.clone();
+            let expected_auth = expected_auth.clone();
+            std::thread::spawn(move || serve_replay(&app, stream, &fixtures, port, &expected_auth));
+        }
+    });
+
+    log_event!(
+        app,
+        Info,
+        "local API replaying fixtures; sidecar not started",
+        dir = dir.display().to_string(),
+        fixtures = fixture_count,
+        port = port
+    );
+    health::report(app, HealthState::Ready, None);
+    Ok(())
+}
+
+pub fn stop_replay(app: &AppHandle) {
+    let Some(capture) = app.try_state::<LocalApiCapture>() else {
+        return;
+    };
+    let Some(server) = capture.replay.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return;
+    };
+    server.stop.store(true, Ordering::SeqCst);
+    // Wake the blocking accept so the listener thread sees the flag and drops the socket.
+    let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port));
+    log_event!(app, Info, "local API replay server stopped", port = server.port);
+}
+
+#[tauri::command]
+pub fn get_local_api_capture_mode(
+    webview: Webview,
+    capture: tauri::State<'_, LocalApiCapture>,
+) -> Result<CaptureMode, String> {
+    require_trusted_window(webview.label())?;
+    Ok(capture.mode())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_capture_flags() {
+        assert_eq!(mode_from_args(args(&["world-monitor"])), Ok(CaptureMode::Off));
+        assert_eq!(
+            mode_from_args(args(&["world-monitor", "--replay-local-api", "/tmp/fx"])),
+            Ok(CaptureMode::Replay { dir: "/tmp/fx".into() })
+        );
+        assert_eq!(
+            mode_from_args(args(&["world-monitor", "--record-local-api=/tmp/fx"])),
+            Ok(CaptureMode::Record { dir: "/tmp/fx".into() })
+        );
+        assert!(mode_from_args(args(&["world-monitor", "--record-local-api"])).is_err());
+        assert!(mode_from_args(args(&["x", "--record-local-api=a", "--replay-local-api=b"])).is_err());
+    }
+
+    #[test]
+    fn window_fixture_dirs_stay_under_the_fixtures_root() {
+        let root = Path::new("/data/world-monitor/fixtures");
+        assert_eq!(fixture_subdir(root, "e2e/markets").unwrap(), root.join("e2e/markets"));
+        assert!(fixture_subdir(root, "../logs").is_err());
+        assert!(fixture_subdir(root, "e2e/../../logs").is_err());
+        assert!(fixture_subdir(root, "/home/user/.ssh").is_err());
+        assert!(fixture_subdir(root, "./e2e").is_err());
+    }
+
+    #[test]
+    fn matches_fixtures_by_canonical_query_and_body() {
+        let dir = std::env::temp_dir().join(format!("wm-replay-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fixture = json!({
+            "version": 1,
+            "recordedAt": "2026-01-01T00:00:00.000Z",
+            "request": { "method": "GET", "path": "/api/market/v1/quotes", "query": "a=1&symbols=SPY", "body": null },
+            "response": { "status": 200, "headers": { "content-type": "application/json" }, "body": "eyJvayI6dHJ1ZX0=" }
+        });
+        fs::write(dir.join("GET-market-v1-quotes-0123.json"), fixture.to_string()).unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let fixtures = load_fixtures(&dir).unwrap();
+        let hit = find_fixture(&fixtures, "get", "/api/market/v1/quotes?symbols=SPY&a=1", b"").unwrap();
+        assert_eq!(hit.body, br#"{"ok":true}"#);
+        assert_eq!(hit.headers.get("content-type").map(String::as_str), Some("application/json"));
+        assert!(find_fixture(&fixtures, "GET", "/api/market/v1/quotes?symbols=QQQ&a=1", b"").is_none());
+        assert!(find_fixture(&fixtures, "POST", "/api/market/v1/quotes?symbols=SPY&a=1", b"").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cors_allows_only_sidecar_origins() {
+        assert_eq!(cors_origin(Some("http://tauri.localhost")), "http://tauri.localhost");
+        assert_eq!(cors_origin(Some("https://preview-1.worldmonitor.app")), "https://preview-1.worldmonitor.app");
+        assert_eq!(cors_origin(Some("https://worldmonitorevil.app")), "tauri://localhost");
+        assert_eq!(cors_origin(None), "tauri://localhost");
+    }
+}
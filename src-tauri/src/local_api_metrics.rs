@@ -0,0 +1,440 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::cloud_fallback::FallbackSample;
+use crate::config::DesktopConfigState;
+use crate::logging::now_ms;
+use crate::loopback_http;
+use crate::require_trusted_window;
+
+/// Upper bounds of the latency buckets, in milliseconds.
+const LATENCY_BOUNDS_MS: [f64; 11] = [5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0];
+/// Upper bounds of the response size buckets, in bytes.
+const SIZE_BOUNDS_BYTES: [f64; 6] = [1024.0, 10240.0, 102400.0, 1048576.0, 10485760.0, 104857600.0];
+const WINDOW_SLOT_SECS: u64 = 60;
+/// The rolling view covers this many one-minute slots.
+const WINDOW_SLOTS: usize = 5;
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Serve Prometheus text at `http://127.0.0.1:<port>/metrics`. Off when unset.
+    pub prometheus_port: Option<u16>,
+}
+
+/// One `/api/*` request as reported by the sidecar.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestSample {
+    /// Route pattern (`/api/[domain]/v1/[rpc]`), a built-in path, or `unmatched`.
+    pub route: String,
+    /// `local` handler, `cloud` fallback, or the `sidecar` itself.
+    pub upstream: String,
+    pub status: u16,
+    pub duration_ms: f64,
+    /// Response body bytes as sent, after compression.
+    pub bytes: u64,
+    // The rest feeds the HAR export; older sidecars omit it.
+    #[serde(default)]
+    pub method: String,
+    /// Full request URL as the sidecar saw it.
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub started_at: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub request_headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub request_bytes: u64,
+    #[serde(default)]
+    pub response_headers: Vec<(String, String)>,
+    /// Set when the request failed locally and the fallback policy applied.
+    #[serde(default)]
+    pub fallback: Option<FallbackSample>,
+}
+
+#[derive(Clone)]
+struct Histogram {
+    /// Per-bucket counts; the last one is `+Inf`.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &[f64]) -> Self {
+        Histogram {
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, bounds: &[f64], value: f64) {
+        let index = bounds.iter().position(|b| value <= *b).unwrap_or(bounds.len());
+        self.counts[index] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn merge(&mut self, other: &Histogram) {
+        for (a, b) in self.counts.iter_mut().zip(&other.counts) {
+            *a += b;
+        }
+        self.sum += other.sum;
+        self.count += other.count;
+    }
+
+    /// Upper bound of the bucket holding quantile `q`. Past the last bound
+    /// this is the largest finite bound, so it understates the tail.
+    fn quantile(&self, bounds: &[f64], q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (q * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(bounds.get(i).or(bounds.last()).copied().unwrap_or(0.0));
+            }
+        }
+        bounds.last().copied()
+    }
+}
+
+#[derive(Clone)]
+struct Series {
+    latency: Histogram,
+    size: Histogram,
+    statuses: BTreeMap<u16, u64>,
+}
+
+impl Default for Series {
+    fn default() -> Self {
+        Series {
+            latency: Histogram::new(&LATENCY_BOUNDS_MS),
+            size: Histogram::new(&SIZE_BOUNDS_BYTES),
+            statuses: BTreeMap::new(),
+        }
+    }
+}
+
+impl Series {
+    fn observe(&mut self, sample: &RequestSample) {
+        self.latency.observe(&LATENCY_BOUNDS_MS, sample.duration_ms.max(0.0));
+        self.size.observe(&SIZE_BOUNDS_BYTES, sample.bytes as f64);
+        *self.statuses.entry(sample.status).or_default() += 1;
+    }
+
+    fn merge(&mut self, other: &Series) {
+        self.latency.merge(&other.latency);
+        self.size.merge(&other.size);
+        for (status, count) in &other.statuses {
+            *self.statuses.entry(*status).or_default() += count;
+        }
+    }
+}
+
+/// `(route, upstream)`.
+type SeriesKey = (String, String);
+
+#[derive(Default)]
+struct MetricsInner {
+    /// Since the app started; what Prometheus scrapes.
+    total: BTreeMap<SeriesKey, Series>,
+    /// Recent one-minute slots, oldest first, keyed by slot start.
+    window: VecDeque<(u64, BTreeMap<SeriesKey, Series>)>,
+}
+
+/// Request metrics for sidecar traffic. Kept by the host so they survive
+/// sidecar restarts.
+#[derive(Default)]
+pub struct LocalApiMetrics {
+    inner: Mutex<MetricsInner>,
+}
+
+fn now_secs() -> u64 {
+    now_ms() / 1000
+}
+
+impl LocalApiMetrics {
+    fn record_at(&self, sample: &RequestSample, now_secs: u64) {
+        let key = (sample.route.clone(), sample.upstream.clone());
+        let slot = now_secs - now_secs % WINDOW_SLOT_SECS;
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.total.entry(key.clone()).or_default().observe(sample);
+        if inner.window.back().map(|(start, _)| *start) != Some(slot) {
+            inner.window.push_back((slot, BTreeMap::new()));
+        }
+        let oldest = slot.saturating_sub(WINDOW_SLOT_SECS * (WINDOW_SLOTS as u64 - 1));
+        while inner.window.front().is_some_and(|(start, _)| *start < oldest) {
+            inner.window.pop_front();
+        }
+        if let Some((_, series)) = inner.window.back_mut() {
+            series.entry(key).or_default().observe(sample);
+        }
+    }
+
+    pub fn record(&self, sample: &RequestSample) {
+        self.record_at(sample, now_secs());
+    }
+
+    fn report_at(&self, now_secs: u64) -> MetricsReport {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let oldest = (now_secs - now_secs % WINDOW_SLOT_SECS).saturating_sub(WINDOW_SLOT_SECS * (WINDOW_SLOTS as u64 - 1));
+        let mut window: BTreeMap<&SeriesKey, Series> = BTreeMap::new();
+        for (_, slot) in inner.window.iter().filter(|(start, _)| *start >= oldest) {
+            for (key, series) in slot {
+                window.entry(key).or_default().merge(series);
+            }
+        }
+        let routes = inner
+            .total
+            .iter()
+            .map(|(key, total)| RouteMetrics {
+                route: key.0.clone(),
+                upstream: key.1.clone(),
+                total: summarize(total),
+                recent: summarize(window.get(key).unwrap_or(&Series::default())),
+            })
+            .collect();
+        MetricsReport {
+            window_secs: WINDOW_SLOT_SECS * WINDOW_SLOTS as u64,
+            routes,
+        }
+    }
+
+    fn prometheus_text(&self) -> String {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        out.push_str("# HELP wm_local_api_requests_total Local API requests by route, upstream and status.\n");
+        out.push_str("# TYPE wm_local_api_requests_total counter\n");
+        for ((route, upstream), series) in &inner.total {
+            for (status, count) in &series.statuses {
+                let _ = writeln!(
+                    out,
+                    "wm_local_api_requests_total{{route=\"{}\",upstream=\"{}\",status=\"{status}\"}} {count}",
+                    escape_label(route),
+                    escape_label(upstream)
+                );
+            }
+        }
+        write_histogram(
+            &mut out,
+            "wm_local_api_request_duration_seconds",
+            "Local API request latency.",
+            &inner.total,
+            |s| &s.latency,
+            &LATENCY_BOUNDS_MS,
+            1000.0,
+        );
+        write_histogram(
+            &mut out,
+            "wm_local_api_response_size_bytes",
+            "Local API response body size as sent.",
+            &inner.total,
+            |s| &s.size,
+            &SIZE_BOUNDS_BYTES,
+            1.0,
+        );
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn write_histogram(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    series: &BTreeMap<SeriesKey, Series>,
+    histogram: impl Fn(&Series) -> &Histogram,
+    bounds: &[f64],
+    divisor: f64,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} histogram");
+    for ((route, upstream), s) in series {
+        let h = histogram(s);
+        let labels = format!("route=\"{}\",upstream=\"{}\"", escape_label(route), escape_label(upstream));
+        let mut cumulative = 0;
+        for (i, count) in h.counts.iter().enumerate() {
+            cumulative += count;
+            let le = bounds.get(i).map(|b| (b / divisor).to_string()).unwrap_or_else(|| "+Inf".to_string());
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{le}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {}", h.sum / divisor);
+        let _ = writeln!(out, "{name}_count{{{labels}}} {}", h.count);
+    }
+}
+
+#[derive(Serialize)]
+pub struct LatencySummary {
+    pub avg: Option<f64>,
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct SeriesSummary {
+    pub requests: u64,
+    /// Count per status code.
+    pub statuses: BTreeMap<u16, u64>,
+    pub latency_ms: LatencySummary,
+    pub bytes_total: u64,
+    pub bytes_p95: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct RouteMetrics {
+    pub route: String,
+    pub upstream: String,
+    /// Since the app started.
+    pub total: SeriesSummary,
+    /// The last `window_secs`.
+    pub recent: SeriesSummary,
+}
+
+#[derive(Serialize)]
+pub struct MetricsReport {
+    pub window_secs: u64,
+    pub routes: Vec<RouteMetrics>,
+}
+
+fn summarize(series: &Series) -> SeriesSummary {
+    let latency = &series.latency;
+    SeriesSummary {
+        requests: latency.count,
+        statuses: series.statuses.clone(),
+        latency_ms: LatencySummary {
+            avg: (latency.count > 0).then(|| latency.sum / latency.count as f64),
+            p50: latency.quantile(&LATENCY_BOUNDS_MS, 0.50),
+            p95: latency.quantile(&LATENCY_BOUNDS_MS, 0.95),
+            p99: latency.quantile(&LATENCY_BOUNDS_MS, 0.99),
+        },
+        bytes_total: series.size.sum as u64,
+        bytes_p95: series.size.quantile(&SIZE_BOUNDS_BYTES, 0.95),
+    }
+}
+
+/// Called for every metric line the sidecar prints.
+pub fn observe(app: &AppHandle, sample: RequestSample) {
+    if let Some(metrics) = app.try_state::<LocalApiMetrics>() {
+        metrics.record(&sample);
+    }
+}
+
+fn serve_scrape(app: &AppHandle, mut stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(SCRAPE_TIMEOUT));
+    let request = match loopback_http::read_request(&mut stream, 0) {
+        Ok(request) => request,
+        Err((status, reason)) => {
+            loopback_http::respond(&mut stream, status, &[("Content-Type", "text/plain")], format!("{reason}\n").as_bytes());
+            return;
+        }
+    };
+    if request.method == "GET" && request.path == "/metrics" {
+        let text = app
+            .try_state::<LocalApiMetrics>()
+            .map(|m| m.prometheus_text())
+            .unwrap_or_default();
+        loopback_http::respond(&mut stream, 200, &[("Content-Type", "text/plain; version=0.0.4")], text.as_bytes());
+    } else {
+        loopback_http::respond(&mut stream, 404, &[("Content-Type", "text/plain")], b"not found\n");
+    }
+}
+
+/// Serve `/metrics` on loopback if `metrics.prometheus_port` is set.
+pub fn start_prometheus_endpoint(app: &AppHandle) -> Result<(), String> {
+    let Some(port) = app
+        .try_state::<DesktopConfigState>()
+        .and_then(|c| c.snapshot().metrics.prometheus_port)
+    else {
+        return Ok(());
+    };
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .map_err(|e| format!("Failed to bind metrics endpoint on 127.0.0.1:{port}: {e}"))?;
+    log_event!(app, Info, "prometheus metrics endpoint listening", port = port);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            serve_scrape(&app, stream);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_local_api_metrics(
+    webview: Webview,
+    metrics: tauri::State<'_, LocalApiMetrics>,
+) -> Result<MetricsReport, String> {
+    require_trusted_window(webview.label())?;
+    Ok(metrics.report_at(now_secs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(route: &str, status: u16, duration_ms: f64, bytes: u64) -> RequestSample {
+        RequestSample {
+            route: route.into(),
+            upstream: "local".into(),
+            status,
+            duration_ms,
+            bytes,
+            method: "GET".into(),
+            url: String::new(),
+            started_at: String::new(),
+            http_version: String::new(),
+            request_headers: Vec::new(),
+            request_bytes: 0,
+            response_headers: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn keeps_totals_and_a_rolling_window() {
+        let metrics = LocalApiMetrics::default();
+        let t0 = 1_700_000_000 - 1_700_000_000 % WINDOW_SLOT_SECS;
+        metrics.record_at(&sample("/api/[domain]/v1/[rpc]", 200, 40.0, 2048), t0);
+        metrics.record_at(&sample("/api/[domain]/v1/[rpc]", 502, 900.0, 64), t0 + 30);
+        metrics.record_at(&sample("/api/[domain]/v1/[rpc]", 200, 8.0, 512), t0 + 600);
+
+        let report = metrics.report_at(t0 + 600);
+        let route = &report.routes[0];
+        assert_eq!(route.total.requests, 3);
+        assert_eq!(route.total.statuses.get(&502), Some(&1));
+        assert_eq!(route.total.bytes_total, 2624);
+        assert_eq!(route.total.latency_ms.p50, Some(50.0));
+        assert_eq!(route.recent.requests, 1);
+        assert_eq!(route.recent.latency_ms.p99, Some(10.0));
+    }
+
+    #[test]
+    fn renders_prometheus_histograms() {
+        let metrics = LocalApiMetrics::default();
+        metrics.record(&sample("/api/rss-proxy", 200, 120.0, 4096));
+        let text = metrics.prometheus_text();
+        assert!(text.contains("wm_local_api_requests_total{route=\"/api/rss-proxy\",upstream=\"local\",status=\"200\"} 1"));
+        assert!(text.contains("wm_local_api_request_duration_seconds_bucket{route=\"/api/rss-proxy\",upstream=\"local\",le=\"0.1\"} 0"));
+        assert!(text.contains("wm_local_api_request_duration_seconds_bucket{route=\"/api/rss-proxy\",upstream=\"local\",le=\"0.25\"} 1"));
+        assert!(text.contains("wm_local_api_response_size_bytes_bucket{route=\"/api/rss-proxy\",upstream=\"local\",le=\"+Inf\"} 1"));
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
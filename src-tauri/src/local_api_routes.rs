@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::cloud_fallback::FallbackMode;
+use crate::local_api_capture;
+use crate::local_api_transport::{self, LocalApiAddr};
+use crate::require_trusted_window;
+use crate::LocalApiState;
+
+/// A probe imports every handler, so allow for a cold start.
+const ROUTES_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteStatus {
+    /// Served by its local handler.
+    Local,
+    /// The local handler failed at runtime and those paths now go to the cloud.
+    Cloud,
+    /// The module failed to import or has no default handler.
+    Broken,
+    /// No request has imported the handler yet.
+    #[serde(rename = "not-loaded")]
+    NotLoaded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteEntry {
+    /// Route pattern, e.g. `/api/[domain]/v1/[rpc]`.
+    pub route: String,
+    /// Higher wins when several patterns match a path.
+    pub priority: u32,
+    /// Handler module, relative to the API dir.
+    pub module: String,
+    pub status: RouteStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Concrete paths that the sidecar now sends to the cloud.
+    #[serde(default)]
+    pub cloud_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteTable {
+    pub api_dir: String,
+    pub cloud_fallback: FallbackMode,
+    /// In match order, highest priority first.
+    pub routes: Vec<RouteEntry>,
+    /// Requested paths that matched no route.
+    #[serde(default)]
+    pub missing: Vec<String>,
+}
+
+fn fetch_route_table(app: &AppHandle, probe: bool) -> Result<RouteTable, String> {
+    if local_api_capture::is_replaying(app) {
+        return Err("Route table is unavailable while replaying fixtures".to_string());
+    }
+    let state = app.state::<LocalApiState>();
+    let addr = LocalApiAddr::current(&state).ok_or_else(|| "Local API is not running".to_string())?;
+    let headers: Vec<(String, String)> = state
+        .token
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|t| vec![("Authorization".to_string(), format!("Bearer {t}"))])
+        .unwrap_or_default();
+    let path = if probe { "/api/local-routes?probe=1" } else { "/api/local-routes" };
+    let response = local_api_transport::send(&addr, "GET", path, &headers, &[], ROUTES_TIMEOUT)
+        .map_err(|e| format!("local-routes request failed: {e}"))?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("local-routes HTTP {}", response.status));
+    }
+    serde_json::from_slice(&response.body).map_err(|e| format!("local-routes invalid JSON: {e}"))
+}
+
+/// The sidecar's `api/**` routes and how each one is being served. With
+/// `probe`, the sidecar first imports every handler it has not loaded yet,
+/// which runs their top-level code.
+#[tauri::command]
+pub async fn get_local_api_routes(webview: Webview, app: AppHandle, probe: Option<bool>) -> Result<RouteTable, String> {
+    require_trusted_window(webview.label())?;
+    let probe = probe.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || fetch_route_table(&app, probe))
+        .await
+        .map_err(|e| format!("Route table task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sidecar_route_table() {
+        let body = r#"{
+            "apiDir": "/opt/world-monitor/api",
+            "cloudFallback": "always",
+            "routes": [
+                { "route": "/api/market/quotes", "priority": 20, "module": "market/quotes.js", "status": "local", "error": null, "cloudPaths": [] },
+                { "route": "/api/[domain]/v1/[rpc]", "priority": 14, "module": "[domain]/v1/[rpc].js", "status": "cloud", "error": null, "cloudPaths": ["/api/news/v1/list"] },
+                { "route": "/api/fred-data", "priority": 10, "module": "fred-data.js", "status": "broken", "error": "missing dependency: x" },
+                { "route": "/api/eia", "priority": 10, "module": "eia.js", "status": "not-loaded", "error": null }
+            ],
+            "missing": ["/api/nowhere"]
+        }"#;
+        let table: RouteTable = serde_json::from_str(body).unwrap();
+
+        assert_eq!(table.cloud_fallback, FallbackMode::Always);
+        assert_eq!(table.routes[0].status, RouteStatus::Local);
+        assert_eq!(table.routes[1].cloud_paths, vec!["/api/news/v1/list".to_string()]);
+        assert_eq!(table.routes[2].status, RouteStatus::Broken);
+        assert_eq!(table.routes[2].error.as_deref(), Some("missing dependency: x"));
+        assert_eq!(table.routes[3].status, RouteStatus::NotLoaded);
+        assert_eq!(table.missing, vec!["/api/nowhere".to_string()]);
+    }
+}
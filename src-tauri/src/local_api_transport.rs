@@ -0,0 +1,329 @@
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager};
+
+use crate::{LocalApiState, TRUSTED_WINDOWS};
+
+/// URI scheme the webview uses to reach a socket-bound sidecar.
+pub const LOCAL_API_SCHEME: &str = "wmapi";
+const BRIDGE_TIMEOUT: Duration = Duration::from_secs(60);
+/// macOS caps `sun_path` at 104 bytes including the terminator.
+#[cfg(unix)]
+const MAX_SOCKET_PATH_LEN: usize = 103;
+
+/// Where the running sidecar is listening.
+#[derive(Clone, Debug)]
+pub enum LocalApiAddr {
+    Tcp(u16),
+    /// Unix domain socket path, or a `\\.\pipe\...` name on Windows.
+    Socket(PathBuf),
+}
+
+impl LocalApiAddr {
+    pub fn current(state: &LocalApiState) -> Option<Self> {
+        if let Some(path) = state.socket.lock().ok().and_then(|g| g.clone()) {
+            return Some(LocalApiAddr::Socket(path));
+        }
+        state.port.lock().ok().and_then(|g| *g).map(LocalApiAddr::Tcp)
+    }
+}
+
+/// Base URL the frontend should prefix `/api/*` paths with. Socket mode goes
+/// through the `wmapi` scheme, which WebView2 exposes as `http://wmapi.localhost`.
+pub fn base_url(addr: &LocalApiAddr) -> String {
+    match addr {
+        LocalApiAddr::Tcp(port) => format!("http://127.0.0.1:{port}"),
+        LocalApiAddr::Socket(_) if cfg!(windows) => format!("http://{LOCAL_API_SCHEME}.localhost"),
+        LocalApiAddr::Socket(_) => format!("{LOCAL_API_SCHEME}://localhost"),
+    }
+}
+
+/// Pick the socket the sidecar should listen on. On Unix it lives in a 0700
+/// `run/` directory under app data so only this user can connect; a stale
+/// socket from a crashed run is removed first. Windows gets a fresh pipe name.
+#[cfg(unix)]
+pub fn prepare_socket_path(app: &AppHandle) -> Result<PathBuf, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?
+        .join("run");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create socket directory {}: {e}", dir.display()))?;
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+        .map_err(|e| format!("Failed to restrict socket directory {}: {e}", dir.display()))?;
+
+    let path = dir.join("local-api.sock");
+    if path.as_os_str().len() > MAX_SOCKET_PATH_LEN {
+        return Err(format!(
+            "Local API socket path is too long for this platform ({} bytes): {}",
+            path.as_os_str().len(),
+            path.display()
+        ));
+    }
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove stale socket {}: {e}", path.display())),
+    }
+    Ok(path)
+}
+
+/// Node's `listen` cannot set a DACL on the pipe, so unlike the Unix socket
+/// it is not owner-only; the random name and the bearer token guard it.
+#[cfg(windows)]
+pub fn prepare_socket_path(_app: &AppHandle) -> Result<PathBuf, String> {
+    let suffix = &crate::generate_local_token()[..16];
+    Ok(PathBuf::from(format!(r"\\.\pipe\world-monitor-local-api-{suffix}")))
+}
+
+trait Stream: Read + Write {}
+impl<T: Read + Write> Stream for T {}
+
+fn connect(addr: &LocalApiAddr, timeout: Duration) -> Result<Box<dyn Stream>, String> {
+    match addr {
+        LocalApiAddr::Tcp(port) => {
+            let target = SocketAddr::from((Ipv4Addr::LOCALHOST, *port));
+            let stream = TcpStream::connect_timeout(&target, timeout)
+                .map_err(|e| format!("connect 127.0.0.1:{port} failed: {e}"))?;
+            let _ = stream.set_read_timeout(Some(timeout));
+            let _ = stream.set_write_timeout(Some(timeout));
+            Ok(Box::new(stream))
+        }
+        #[cfg(unix)]
+        LocalApiAddr::Socket(path) => {
+            let stream = std::os::unix::net::UnixStream::connect(path)
+                .map_err(|e| format!("connect {} failed: {e}", path.display()))?;
+            let _ = stream.set_read_timeout(Some(timeout));
+            let _ = stream.set_write_timeout(Some(timeout));
+            Ok(Box::new(stream))
+        }
+        #[cfg(windows)]
+        LocalApiAddr::Socket(path) => {
+            // Named pipes open like files; std offers no timeouts for them.
+            let pipe = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .map_err(|e| format!("open {} failed: {e}", path.display()))?;
+            Ok(Box::new(pipe))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LocalApiResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl LocalApiResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// One HTTP/1.1 exchange with the sidecar over TCP or a socket. Sends
+/// `Connection: close` and reads until EOF, which keeps the client trivial;
+/// every call opens a new connection.
+pub fn send(
+    addr: &LocalApiAddr,
+    method: &str,
+    path_and_query: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+    timeout: Duration,
+) -> Result<LocalApiResponse, String> {
+    let mut stream = connect(addr, timeout)?;
+    let mut head = format!("{method} {path_and_query} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(body))
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("request write failed: {e}"))?;
+
+    let mut raw = Vec::new();
+    stream
+        .read_to_end(&mut raw)
+        .map_err(|e| format!("response read failed: {e}"))?;
+    parse_response(&raw)
+}
+
+fn parse_response(raw: &[u8]) -> Result<LocalApiResponse, String> {
+    let head_end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| "malformed response: no header terminator".to_string())?;
+    let head = std::str::from_utf8(&raw[..head_end])
+        .map_err(|_| "malformed response: non-UTF-8 headers".to_string())?;
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| "malformed response: bad status line".to_string())?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let mut response = LocalApiResponse {
+        status,
+        headers,
+        body: Vec::new(),
+    };
+    let rest = &raw[head_end + 4..];
+    let chunked = response
+        .header("transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"));
+    response.body = if chunked {
+        decode_chunked(rest)?
+    } else if let Some(len) = response.header("content-length").and_then(|v| v.parse::<usize>().ok()) {
+        rest[..len.min(rest.len())].to_vec()
+    } else {
+        rest.to_vec()
+    };
+    Ok(response)
+}
+
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = data
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| "malformed chunked body".to_string())?;
+        let size_line = std::str::from_utf8(&data[..line_end]).unwrap_or("");
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| format!("malformed chunk size '{size_hex}'"))?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        if data.len() < size {
+            return Err("truncated chunked body".to_string());
+        }
+        body.extend_from_slice(&data[..size]);
+        data = data.get(size + 2..).unwrap_or(&[]);
+    }
+}
+
+/// Headers that describe the hop, not the payload; the bridge re-frames both ways.
+fn is_hop_header(name: &str) -> bool {
+    ["host", "connection", "content-length", "transfer-encoding", "keep-alive", "authorization"]
+        .iter()
+        .any(|h| name.eq_ignore_ascii_case(h))
+}
+
+fn bridge_error(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(serde_json::json!({ "error": message }).to_string().into_bytes())
+        .unwrap_or_default()
+}
+
+/// Forward a `wmapi://` request from a trusted webview onto the sidecar's
+/// socket. The bridge supplies the bearer token itself, so pages never need
+/// it to reach the API and other windows cannot borrow it.
+pub fn bridge_request(app: &AppHandle, webview_label: &str, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    if !TRUSTED_WINDOWS.contains(&webview_label) {
+        return bridge_error(StatusCode::FORBIDDEN, "window not allowed to reach the local API");
+    }
+    let state = app.state::<LocalApiState>();
+    let addr = match LocalApiAddr::current(&state) {
+        Some(addr @ LocalApiAddr::Socket(_)) => addr,
+        Some(LocalApiAddr::Tcp(_)) => {
+            return bridge_error(StatusCode::NOT_FOUND, "local API is not using socket transport")
+        }
+        None => return bridge_error(StatusCode::SERVICE_UNAVAILABLE, "local API is not running"),
+    };
+    let token = state.token.lock().ok().and_then(|g| g.clone());
+
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "/".to_string());
+    let mut headers: Vec<(String, String)> = request
+        .headers()
+        .iter()
+        .filter(|(name, _)| !is_hop_header(name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    if let Some(token) = token {
+        headers.push(("Authorization".to_string(), format!("Bearer {token}")));
+    }
+
+    match send(
+        &addr,
+        request.method().as_str(),
+        &path_and_query,
+        &headers,
+        request.body(),
+        BRIDGE_TIMEOUT,
+    ) {
+        Ok(upstream) => {
+            let mut builder = Response::builder().status(upstream.status);
+            for (name, value) in upstream.headers.iter().filter(|(name, _)| !is_hop_header(name)) {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+            builder
+                .body(upstream.body)
+                .unwrap_or_else(|_| bridge_error(StatusCode::BAD_GATEWAY, "invalid upstream response"))
+        }
+        Err(err) => {
+            log_event!(
+                app,
+                Warn,
+                "local API bridge request failed",
+                method = request.method().as_str(),
+                path = path_and_query,
+                error = err
+            );
+            bridge_error(StatusCode::BAD_GATEWAY, &err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_content_length_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        assert_eq!(response.body, b"{}");
+    }
+
+    #[test]
+    fn decodes_chunked_response() {
+        let raw = b"HTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nnot \r\n5\r\nfound\r\n0\r\n\r\n";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body, b"not found");
+    }
+
+    #[test]
+    fn rejects_truncated_headers() {
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 2").is_err());
+    }
+}
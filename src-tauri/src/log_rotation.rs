@@ -0,0 +1,192 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{desktop_json_log_path, desktop_log_path, sidecar_log_path};
+
+/// Size/age limits shared by `desktop.log` and `local-api.log`. Rotated
+/// generations are `<name>.1` (newest) through `<name>.<keep_files>`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogRotationPolicy {
+    /// Rotate before a write would push the live file past this size. 0 disables.
+    pub max_file_bytes: u64,
+    /// Rotate once the live file is older than this. 0 disables.
+    pub max_file_age_hours: u64,
+    /// Rotated generations to keep; older ones are deleted.
+    pub keep_files: u32,
+}
+
+impl Default for LogRotationPolicy {
+    fn default() -> Self {
+        LogRotationPolicy {
+            max_file_bytes: 10 * 1024 * 1024,
+            max_file_age_hours: 24,
+            keep_files: 5,
+        }
+    }
+}
+
+/// Append-only log file that rotates itself. The handle stays open between
+/// writes; every rotation check happens on the writer's thread under the
+/// owning mutex, so concurrent writers never race a rename.
+pub struct RotatingLog {
+    path: PathBuf,
+    policy: LogRotationPolicy,
+    file: Option<File>,
+    size: u64,
+    opened_at: SystemTime,
+}
+
+fn rotated_path(path: &Path, generation: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{generation}"));
+    PathBuf::from(name)
+}
+
+impl RotatingLog {
+    pub fn new(path: PathBuf, policy: LogRotationPolicy) -> Self {
+        RotatingLog {
+            path,
+            policy,
+            file: None,
+            size: 0,
+            opened_at: SystemTime::now(),
+        }
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let meta = file.metadata()?;
+        self.size = meta.len();
+        // An inherited file carries its age across restarts; `created` is not
+        // available everywhere, so fall back to the last write.
+        self.opened_at = meta
+            .created()
+            .or_else(|_| meta.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn too_old(&self) -> bool {
+        let hours = self.policy.max_file_age_hours;
+        hours > 0
+            && self.size > 0
+            && SystemTime::now()
+                .duration_since(self.opened_at)
+                .unwrap_or_default()
+                > Duration::from_secs(hours.saturating_mul(3600))
+    }
+
+    fn too_big(&self, incoming: u64) -> bool {
+        let max = self.policy.max_file_bytes;
+        max > 0 && self.size > 0 && self.size + incoming > max
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        let keep = self.policy.keep_files;
+        if keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, keep));
+            for generation in (1..keep).rev() {
+                let from = rotated_path(&self.path, generation);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, generation + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.open()?;
+        // `created` on a reused inode can be stale; the fresh file starts now.
+        self.opened_at = SystemTime::now();
+        Ok(())
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.file.is_none() {
+            self.open()?;
+        }
+        if self.too_big(bytes.len() as u64) || self.too_old() {
+            // A failed rotation must not lose the line; keep appending.
+            if self.rotate().is_err() && self.file.is_none() {
+                self.open()?;
+            }
+        }
+        let file = self.file.as_mut().expect("log file opened above");
+        file.write_all(bytes)?;
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// Process-wide handles for the host-managed logs. The sidecar log is
+/// shared by every sidecar generation so restarts keep rotating one file.
+/// Files are opened on first write, so an unused `json` sink never appears.
+pub struct LogSinks {
+    pub desktop: Mutex<RotatingLog>,
+    pub json: Mutex<RotatingLog>,
+    pub local_api: Mutex<RotatingLog>,
+}
+
+impl LogSinks {
+    pub fn new(app: &AppHandle, policy: &LogRotationPolicy) -> Result<Self, String> {
+        Ok(LogSinks {
+            desktop: Mutex::new(RotatingLog::new(desktop_log_path(app)?, policy.clone())),
+            json: Mutex::new(RotatingLog::new(desktop_json_log_path(app)?, policy.clone())),
+            local_api: Mutex::new(RotatingLog::new(sidecar_log_path(app)?, policy.clone())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wm-log-rotation-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rotates_on_size_and_keeps_limited_generations() {
+        let dir = temp_dir("size");
+        let path = dir.join("app.log");
+        let mut log = RotatingLog::new(
+            path.clone(),
+            LogRotationPolicy { max_file_bytes: 10, max_file_age_hours: 0, keep_files: 2 },
+        );
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            log.write(line.as_bytes()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "dddddddd\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "cccccccc\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(), "bbbbbbbb\n");
+        assert!(!rotated_path(&path, 3).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keep_zero_truncates_instead_of_renaming() {
+        let dir = temp_dir("keep-zero");
+        let path = dir.join("app.log");
+        let mut log = RotatingLog::new(
+            path.clone(),
+            LogRotationPolicy { max_file_bytes: 4, max_file_age_hours: 0, keep_files: 0 },
+        );
+        log.write(b"one\n").unwrap();
+        log.write(b"two\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two\n");
+        assert!(!rotated_path(&path, 1).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::logging::Level;
+use crate::{desktop_log_path, require_trusted_window, sidecar_log_path};
+
+pub const LOG_LINE_EVENT: &str = "log-line";
+const DEFAULT_TAIL_LINES: usize = 200;
+const MAX_TAIL_LINES: usize = 5000;
+/// Only the end of the file is scanned; rotation keeps live files near this size anyway.
+const TAIL_SCAN_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogSource {
+    Desktop,
+    LocalApi,
+}
+
+/// `level` keeps lines at that severity or worse; lines without a
+/// recognizable level (most sidecar output) count as `info`. `contains` is a
+/// case-insensitive substring match.
+#[derive(Clone, Default, Deserialize)]
+pub struct LogFilter {
+    pub level: Option<Level>,
+    pub contains: Option<String>,
+}
+
+impl LogFilter {
+    fn matches(&self, line: &str) -> bool {
+        if let Some(max) = self.level {
+            if line_level(line).unwrap_or(Level::Info) > max {
+                return false;
+            }
+        }
+        match &self.contains {
+            Some(needle) if !needle.is_empty() => line.to_lowercase().contains(&needle.to_lowercase()),
+            _ => true,
+        }
+    }
+}
+
+/// First `[LEVEL]` token on the line. Older `[secs][LEVEL]` lines work too.
+fn line_level(line: &str) -> Option<Level> {
+    line.split('[')
+        .skip(1)
+        .filter_map(|part| part.split_once(']').map(|(token, _)| token))
+        .find_map(Level::parse)
+}
+
+fn tail_lines(path: &Path, count: usize, filter: &LogFilter) -> Result<Vec<String>, String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to open {}: {e}", path.display())),
+    };
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to stat {}: {e}", path.display()))?
+        .len();
+    let start = len.saturating_sub(TAIL_SCAN_BYTES);
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| format!("Failed to seek {}: {e}", path.display()))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut lines: Vec<&str> = text.lines().collect();
+    if start > 0 && !lines.is_empty() {
+        // We seeked into the middle of a line.
+        lines.remove(0);
+    }
+    let matched: Vec<String> = lines
+        .into_iter()
+        .filter(|line| filter.matches(line))
+        .map(str::to_string)
+        .collect();
+    let skip = matched.len().saturating_sub(count);
+    Ok(matched.into_iter().skip(skip).collect())
+}
+
+fn source_path(app: &AppHandle, source: LogSource) -> Result<std::path::PathBuf, String> {
+    match source {
+        LogSource::Desktop => desktop_log_path(app),
+        LogSource::LocalApi => sidecar_log_path(app),
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct LogLine<'a> {
+    source: LogSource,
+    line: &'a str,
+}
+
+/// Live-tail subscriptions, keyed by source and webview label.
+#[derive(Default)]
+pub struct LogStreams {
+    subscribers: Mutex<HashMap<(LogSource, String), LogFilter>>,
+}
+
+/// Push a freshly written line to subscribed windows. Called from the log
+/// writers, so it must stay cheap when nobody is listening and must not log.
+pub fn publish(app: &AppHandle, source: LogSource, line: &[u8]) {
+    let Some(streams) = app.try_state::<LogStreams>() else {
+        return;
+    };
+    let mut subscribers = streams.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+    if subscribers.is_empty() {
+        return;
+    }
+    // Forget windows that closed without unsubscribing.
+    subscribers.retain(|(_, label), _| app.get_webview_window(label).is_some());
+
+    let text = String::from_utf8_lossy(line);
+    let text = text.trim_end_matches(['\r', '\n']);
+    let targets: Vec<String> = subscribers
+        .iter()
+        .filter(|((s, _), filter)| *s == source && filter.matches(text))
+        .map(|((_, label), _)| label.clone())
+        .collect();
+    drop(subscribers);
+
+    for label in targets {
+        let _ = app.emit_to(label.as_str(), LOG_LINE_EVENT, LogLine { source, line: text });
+    }
+}
+
+/// Last `lines` (default 200, max 5000) matching lines of a host log.
+#[tauri::command]
+pub async fn tail_log(
+    webview: Webview,
+    app: AppHandle,
+    source: LogSource,
+    lines: Option<usize>,
+    filter: Option<LogFilter>,
+) -> Result<Vec<String>, String> {
+    require_trusted_window(webview.label())?;
+    let count = lines.unwrap_or(DEFAULT_TAIL_LINES).min(MAX_TAIL_LINES);
+    tail_lines(&source_path(&app, source)?, count, &filter.unwrap_or_default())
+}
+
+/// Stream new lines of `source` to the calling window as `log-line` events.
+/// Subscribing again replaces the filter.
+#[tauri::command]
+pub fn subscribe_log_stream(
+    webview: Webview,
+    streams: tauri::State<'_, LogStreams>,
+    source: LogSource,
+    filter: Option<LogFilter>,
+) -> Result<(), String> {
+    require_trusted_window(webview.label())?;
+    streams
+        .subscribers
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert((source, webview.label().to_string()), filter.unwrap_or_default());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unsubscribe_log_stream(
+    webview: Webview,
+    streams: tauri::State<'_, LogStreams>,
+    source: LogSource,
+) -> Result<(), String> {
+    require_trusted_window(webview.label())?;
+    streams
+        .subscribers
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(source, webview.label().to_string()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_filter_keeps_worse_severities() {
+        let filter = LogFilter { level: Some(Level::Warn), contains: None };
+        assert!(filter.matches("2026-01-01T00:00:00.000Z [ERROR] world_monitor: boom"));
+        assert!(filter.matches("[1700000000][WARN] legacy line"));
+        assert!(!filter.matches("2026-01-01T00:00:00.000Z [INFO] world_monitor: fine"));
+        assert!(!filter.matches("[local-api] listening on socket"));
+    }
+
+    #[test]
+    fn tails_last_matching_lines() {
+        let path = std::env::temp_dir().join(format!("wm-log-tail-{}.log", std::process::id()));
+        std::fs::write(&path, "a [INFO] one\nb [ERROR] Two\nc [INFO] three\nd [ERROR] four\n").unwrap();
+
+        let all = tail_lines(&path, 2, &LogFilter::default()).unwrap();
+        assert_eq!(all, vec!["c [INFO] three", "d [ERROR] four"]);
+
+        let filter = LogFilter { level: None, contains: Some("two".into()) };
+        assert_eq!(tail_lines(&path, 10, &filter).unwrap(), vec!["b [ERROR] Two"]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
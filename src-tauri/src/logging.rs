@@ -0,0 +1,289 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::config::DesktopConfigState;
+use crate::log_rotation::LogSinks;
+use crate::log_tail::{self, LogSource};
+use crate::redact::redact_log;
+use crate::require_trusted_window;
+
+/// Log through [`log`] with the calling module as the target:
+/// `log_event!(app, Info, format!("sidecar started"), pid = child.id())`.
+macro_rules! log_event {
+    ($app:expr, $level:ident, $message:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::logging::log(
+            $app,
+            $crate::logging::Level::$level,
+            module_path!(),
+            ::std::convert::AsRef::<str>::as_ref(&$message),
+            &[$((stringify!($key), ::serde_json::json!($value))),*],
+        )
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+
+    /// Lenient parse for the `"INFO"`-style strings older call sites pass.
+    pub fn parse(value: &str) -> Option<Level> {
+        match value.to_ascii_uppercase().as_str() {
+            "ERROR" => Some(Level::Error),
+            "WARN" | "WARNING" => Some(Level::Warn),
+            "INFO" => Some(Level::Info),
+            "DEBUG" => Some(Level::Debug),
+            "TRACE" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Level {
+        match value {
+            1 => Level::Error,
+            2 => Level::Warn,
+            4 => Level::Debug,
+            5 => Level::Trace,
+            _ => Level::Info,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Records below this level are dropped.
+    pub level: Level,
+    /// Also write every record as one JSON object per line to `desktop.jsonl`.
+    pub json_file: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: Level::Info,
+            json_file: false,
+        }
+    }
+}
+
+/// Live filter settings, kept outside the config mutex because every log
+/// call reads them.
+pub struct Logger {
+    level: AtomicU8,
+    json_file: AtomicBool,
+}
+
+impl Logger {
+    pub fn new(config: &LoggingConfig) -> Self {
+        Logger {
+            level: AtomicU8::new(config.level as u8),
+            json_file: AtomicBool::new(config.json_file),
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        Level::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    fn set_level(&self, level: Level) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+}
+
+/// UTC calendar fields `(year, month, day, hour, minute, second)` for a Unix time.
+pub fn utc_date_time(unix_secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (unix_secs / 86_400) as i64;
+    let rem = (unix_secs % 86_400) as u32;
+
+    // Civil-from-days (Howard Hinnant), valid for the whole u64-ms range we see.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, rem / 3600, (rem % 3600) / 60, rem % 60)
+}
+
+/// Milliseconds since the Unix epoch, or 0 if the clock is before it.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// RFC 3339 UTC timestamp with millisecond precision, e.g. `2026-01-31T09:05:00.123Z`.
+pub fn iso_timestamp(since_epoch_ms: u64) -> String {
+    let (year, month, day, hour, minute, second) = utc_date_time(since_epoch_ms / 1000);
+    let millis = since_epoch_ms % 1000;
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{millis:03}Z")
+}
+
+fn format_text(ts: &str, level: Level, target: &str, message: &str, fields: &[(&str, Value)]) -> String {
+    let mut line = format!("{ts} [{}] {target}: {message}", level.as_str());
+    for (key, value) in fields {
+        match value {
+            Value::String(s) => line.push_str(&format!(" {key}={s:?}")),
+            other => line.push_str(&format!(" {key}={other}")),
+        }
+    }
+    line.push('\n');
+    line
+}
+
+fn format_json(ts: &str, level: Level, target: &str, message: &str, fields: &[(&str, Value)]) -> String {
+    let mut record = Map::new();
+    record.insert("ts".into(), Value::String(ts.to_string()));
+    record.insert("level".into(), serde_json::to_value(level).unwrap_or(Value::Null));
+    record.insert("target".into(), Value::String(target.to_string()));
+    record.insert("message".into(), Value::String(message.to_string()));
+    if !fields.is_empty() {
+        let fields: Map<String, Value> = fields.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+        record.insert("fields".into(), Value::Object(fields));
+    }
+    let mut line = Value::Object(record).to_string();
+    line.push('\n');
+    line
+}
+
+/// Write one record to `desktop.log` (and `desktop.jsonl` when enabled).
+/// Secrets are masked before the record reaches any output. Debug builds
+/// mirror it to stderr for `tauri dev`.
+pub fn log(app: &AppHandle, level: Level, target: &str, message: &str, fields: &[(&str, Value)]) {
+    let min_level = app.try_state::<Logger>().map(|l| l.level()).unwrap_or(Level::Info);
+    if level > min_level {
+        return;
+    }
+    write_record(app, level, target, message, fields);
+}
+
+/// Write a record whatever the minimum level is.
+fn write_record(app: &AppHandle, level: Level, target: &str, message: &str, fields: &[(&str, Value)]) {
+    let logger = app.try_state::<Logger>();
+    let ts = iso_timestamp(now_ms());
+    let text = redact_log(&format_text(&ts, level, target, message, fields));
+    if cfg!(debug_assertions) {
+        eprint!("{text}");
+    }
+
+    let Some(sinks) = app.try_state::<LogSinks>() else {
+        // Before setup, or when the rotating sinks could not be opened.
+        if let Ok(path) = crate::desktop_log_path(app) {
+            append_unrotated(&path, text.as_bytes());
+        }
+        return;
+    };
+    let _ = sinks
+        .desktop
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .write(text.as_bytes());
+    log_tail::publish(app, LogSource::Desktop, text.as_bytes());
+    if logger.is_some_and(|l| l.json_file.load(Ordering::Relaxed)) {
+        let json = redact_log(&format_json(&ts, level, target, message, fields));
+        let _ = sinks
+            .json
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .write(json.as_bytes());
+    }
+}
+
+/// Append to a log file without rotation. Used only when `LogSinks` is not
+/// available, so no line is lost.
+pub fn append_unrotated(path: &Path, bytes: &[u8]) {
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = file.write_all(bytes);
+    }
+}
+
+#[tauri::command]
+pub fn get_log_level(webview: Webview, logger: tauri::State<'_, Logger>) -> Result<Level, String> {
+    require_trusted_window(webview.label())?;
+    Ok(logger.level())
+}
+
+/// Change the minimum level immediately and persist it for the next launch.
+#[tauri::command]
+pub fn set_log_level(
+    webview: Webview,
+    app: AppHandle,
+    logger: tauri::State<'_, Logger>,
+    config: tauri::State<'_, DesktopConfigState>,
+    level: Level,
+) -> Result<Level, String> {
+    require_trusted_window(webview.label())?;
+    config.update(&app, |c| c.logging.level = level)?;
+    logger.set_level(level);
+    // Bypass the filter so raising the level to `error` still records the change.
+    write_record(
+        &app,
+        Level::Warn,
+        module_path!(),
+        "log level changed",
+        &[("level", json!(level)), ("window", json!(webview.label()))],
+    );
+    Ok(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn formats_iso_timestamps_in_utc() {
+        assert_eq!(iso_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso_timestamp(951_782_400_123), "2000-02-29T00:00:00.123Z");
+        assert_eq!(iso_timestamp(1_790_000_000_000), "2026-09-21T14:13:20.000Z");
+    }
+
+    #[test]
+    fn text_and_json_lines_carry_fields() {
+        let fields = [("pid", json!(42)), ("window", json!("main"))];
+        let text = format_text("T", Level::Warn, "world_monitor::health", "probe failed", &fields);
+        assert_eq!(text, "T [WARN] world_monitor::health: probe failed pid=42 window=\"main\"\n");
+
+        let parsed: Value =
+            serde_json::from_str(&format_json("T", Level::Warn, "world_monitor::health", "probe failed", &fields)).unwrap();
+        assert_eq!(parsed["level"], "warn");
+        assert_eq!(parsed["fields"]["pid"], 42);
+    }
+
+    #[test]
+    fn parses_legacy_level_strings() {
+        assert_eq!(Level::parse("INFO"), Some(Level::Info));
+        assert_eq!(Level::parse("warning"), Some(Level::Warn));
+        assert_eq!(Level::parse("verbose"), None);
+        assert!(Level::Debug > Level::Info);
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::{Read, Write};
+
+use tauri::http::StatusCode;
+
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// One request received by a loopback endpoint: the host bridge, the egress
+/// proxy, the replay server or the metrics endpoint.
+pub struct HttpRequest {
+    pub method: String,
+    /// Request target, including any query string.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// First header called `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Read a request head. Returns the request with an empty body, plus any
+/// bytes the client sent after the head. `Err` carries the status to answer
+/// with and why.
+pub fn read_head<S: Read>(stream: &mut S) -> Result<(HttpRequest, Vec<u8>), (u16, String)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err((431, "request head too large".to_string()));
+        }
+        match stream.read(&mut chunk) {
+            Ok(0) => return Err((400, "connection closed before request head".to_string())),
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(e) => return Err((408, format!("reading request head failed: {e}"))),
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut parts = lines.next().unwrap_or_default().split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let request = HttpRequest {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+    Ok((request, buf[head_end..].to_vec()))
+}
+
+/// Read one HTTP/1.1 request with a `Content-Length` body of at most
+/// `max_body` bytes.
+pub fn read_request<S: Read>(stream: &mut S, max_body: usize) -> Result<HttpRequest, (u16, String)> {
+    let (mut request, mut body) = read_head(stream)?;
+    let content_length: usize = match request.header("content-length") {
+        Some(value) => value.parse().map_err(|_| (400, "invalid Content-Length".to_string()))?,
+        None => 0,
+    };
+    if content_length > max_body {
+        return Err((413, "request body too large".to_string()));
+    }
+    let mut chunk = [0u8; 4096];
+    while body.len() < content_length {
+        match stream.read(&mut chunk) {
+            Ok(0) => return Err((400, "connection closed before request body".to_string())),
+            Ok(n) => body.extend_from_slice(&chunk[..n]),
+            Err(e) => return Err((408, format!("reading request body failed: {e}"))),
+        }
+    }
+    body.truncate(content_length);
+    request.body = body;
+    Ok(request)
+}
+
+/// Write a complete response and mark the connection for closing.
+pub fn respond<S: Write>(stream: &mut S, status: u16, headers: &[(&str, &str)], body: &[u8]) {
+    let reason = StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Unknown");
+    let mut head = format!("HTTP/1.1 {status} {reason}\r\n");
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", body.len()));
+    let _ = stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(body));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_requests_and_writes_responses() {
+        let raw = b"POST /call?x=1 HTTP/1.1\r\nAuthorization: Bearer t\r\ncontent-length: 5\r\n\r\nhello extra";
+        let request = read_request(&mut &raw[..], 64).unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/call?x=1"));
+        assert_eq!(request.header("authorization"), Some("Bearer t"));
+        assert_eq!(request.body, b"hello");
+        assert_eq!(read_request(&mut &raw[..], 4).err().map(|e| e.0), Some(413));
+        assert_eq!(read_request(&mut &b"GET / HTTP/1.1\r\n"[..], 64).err().map(|e| e.0), Some(400));
+
+        let (head, rest) = read_head(&mut &b"CONNECT api.example.com:443 HTTP/1.1\r\n\r\n\x16\x03"[..]).unwrap();
+        assert_eq!(head.path, "api.example.com:443");
+        assert_eq!(rest, b"\x16\x03");
+
+        let mut out = Vec::new();
+        respond(&mut out, 403, &[("X-Test", "1")], b"no");
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "HTTP/1.1 403 Forbidden\r\nX-Test: 1\r\nContent-Length: 2\r\nConnection: close\r\n\r\nno"
+        );
+    }
+}
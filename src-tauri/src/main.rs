@@ -1,28 +1,78 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+// Declared first so `log_event!` is visible to every module below.
+#[macro_use]
+mod logging;
+
+mod cloud_fallback;
+mod config;
+mod crash;
+mod diagnostics;
+mod egress_proxy;
+mod health;
+mod host_bridge;
+mod js_runtime;
+mod local_api_capture;
+mod local_api_metrics;
+mod local_api_routes;
+mod local_api_transport;
+mod log_rotation;
+mod log_tail;
+mod loopback_http;
+mod redact;
+mod resource_limits;
+mod retention;
+mod sandbox;
+mod sidecar_io;
+mod sidecar_lock;
+mod supervisor;
+mod traffic_har;
+
 use std::collections::HashMap;
 use std::env;
-use std::fs::{self, File, OpenOptions};
-use std::io::Write;
+use std::fs::{self, File};
+use std::net::{Ipv4Addr, TcpListener};
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::Instant;
 
 use keyring::Entry;
 use reqwest::Url;
 use serde::Serialize;
 use serde_json::{Map, Value};
 use tauri::menu::{AboutMetadata, Menu, MenuItem, PredefinedMenuItem, Submenu};
-use tauri::{AppHandle, Manager, RunEvent, Webview, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tauri::{AppHandle, Emitter, Manager, RunEvent, Webview, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+use cloud_fallback::CloudFallbackStats;
+use config::{DesktopConfigState, LocalApiTransport};
+use egress_proxy::EgressProxy;
+use js_runtime::JsRuntime;
+use health::{HealthMonitor, HealthState};
+use host_bridge::HostBridge;
+use local_api_capture::{CaptureMode, LocalApiCapture};
+use local_api_metrics::LocalApiMetrics;
+use local_api_transport::LocalApiAddr;
+use log_rotation::LogSinks;
+use log_tail::{LogSource, LogStreams};
+use logging::Logger;
+use resource_limits::LimitMonitor;
+use sandbox::{SandboxPaths, SandboxStatus};
+use supervisor::SupervisorState;
+use traffic_har::TrafficLog;
 
 const DEFAULT_LOCAL_API_PORT: u16 = 46123;
+const LOCAL_API_HANDSHAKE_TIMEOUT_SECS: u64 = 15;
 const KEYRING_SERVICE: &str = "world-monitor";
 const LOCAL_API_LOG_FILE: &str = "local-api.log";
 const DESKTOP_LOG_FILE: &str = "desktop.log";
+const DESKTOP_JSON_LOG_FILE: &str = "desktop.jsonl";
 const MENU_FILE_SETTINGS_ID: &str = "file.settings";
+const MENU_FILE_RESTART_LOCAL_API_ID: &str = "file.restart-local-api";
+const LOCAL_API_RESTARTED_EVENT: &str = "local-api-restarted";
 const MENU_HELP_GITHUB_ID: &str = "help.github";
 #[cfg(feature = "devtools")]
 const MENU_HELP_DEVTOOLS_ID: &str = "help.devtools";
@@ -57,6 +107,35 @@ struct LocalApiState {
     child: Mutex<Option<Child>>,
     token: Mutex<Option<String>>,
     port: Mutex<Option<u16>>,
+    /// Socket path (or pipe name) when the sidecar uses socket transport.
+    socket: Mutex<Option<PathBuf>>,
+    started_at: Mutex<Option<Instant>>,
+    /// Sandbox outcome for the current sidecar.
+    sandbox: Mutex<Option<SandboxStatus>>,
+    /// Runtime and executable the current sidecar was spawned with.
+    runtime: Mutex<Option<(JsRuntime, PathBuf)>>,
+    /// True while `start_local_api` waits for the handshake. The supervisor
+    /// leaves the child alone until then; start-up failures are handled by
+    /// the caller.
+    starting: Mutex<bool>,
+}
+
+/// Clears `LocalApiState::starting` however `start_local_api` returns.
+struct StartingGuard<'a>(&'a LocalApiState);
+
+impl<'a> StartingGuard<'a> {
+    /// Call with the child slot locked, so the supervisor never sees the
+    /// child without the flag.
+    fn new(state: &'a LocalApiState) -> Self {
+        *state.starting.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        StartingGuard(state)
+    }
+}
+
+impl Drop for StartingGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.starting.lock().unwrap_or_else(|e| e.into_inner()) = false;
+    }
 }
 
 /// In-memory cache for keychain secrets. Populated once at startup to avoid
@@ -176,11 +255,21 @@ impl PersistentCache {
     }
 }
 
+/// Where the frontend's fetch shim should send `/api/*` requests after a restart.
+#[derive(Clone, Serialize)]
+struct LocalApiEndpoint {
+    /// `None` when the sidecar is on a socket and `base_url` is the bridge.
+    port: Option<u16>,
+    base_url: String,
+    token: String,
+}
+
 #[derive(Serialize)]
 struct DesktopRuntimeInfo {
     os: String,
     arch: String,
     local_api_port: Option<u16>,
+    local_api_sandbox: Option<SandboxStatus>,
 }
 
 fn save_vault(cache: &HashMap<String, String>) -> Result<(), String> {
@@ -222,11 +311,16 @@ fn get_local_api_token(webview: Webview, state: tauri::State<'_, LocalApiState>)
 
 #[tauri::command]
 fn get_desktop_runtime_info(state: tauri::State<'_, LocalApiState>) -> DesktopRuntimeInfo {
+    desktop_runtime_info(&state)
+}
+
+fn desktop_runtime_info(state: &LocalApiState) -> DesktopRuntimeInfo {
     let port = state.port.lock().ok().and_then(|g| *g);
     DesktopRuntimeInfo {
         os: env::consts::OS.to_string(),
         arch: env::consts::ARCH.to_string(),
         local_api_port: port,
+        local_api_sandbox: state.sandbox.lock().ok().and_then(|g| g.clone()),
     }
 }
 
@@ -238,6 +332,14 @@ fn get_local_api_port(webview: Webview, state: tauri::State<'_, LocalApiState>)
         .ok_or_else(|| "Port not yet assigned".to_string())
 }
 
+#[tauri::command]
+fn get_local_api_base_url(webview: Webview, state: tauri::State<'_, LocalApiState>) -> Result<String, String> {
+    require_trusted_window(webview.label())?;
+    LocalApiAddr::current(&state)
+        .map(|addr| local_api_transport::base_url(&addr))
+        .ok_or_else(|| "Local API not yet listening".to_string())
+}
+
 #[tauri::command]
 fn list_supported_secret_keys() -> Vec<String> {
     SUPPORTED_SECRET_KEYS
@@ -276,6 +378,7 @@ fn get_all_secrets(webview: Webview, cache: tauri::State<'_, SecretsCache>) -> R
 #[tauri::command]
 fn set_secret(
     webview: Webview,
+    app: AppHandle,
     key: String,
     value: String,
     cache: tauri::State<'_, SecretsCache>,
@@ -298,11 +401,18 @@ fn set_secret(
     }
     save_vault(&proposed)?;
     *secrets = proposed;
+    drop(secrets);
+    redact::refresh(&app);
     Ok(())
 }
 
 #[tauri::command]
-fn delete_secret(webview: Webview, key: String, cache: tauri::State<'_, SecretsCache>) -> Result<(), String> {
+fn delete_secret(
+    webview: Webview,
+    app: AppHandle,
+    key: String,
+    cache: tauri::State<'_, SecretsCache>,
+) -> Result<(), String> {
     require_trusted_window(webview.label())?;
     if !SUPPORTED_SECRET_KEYS.contains(&key.as_str()) {
         return Err(format!("Unsupported secret key: {key}"));
@@ -315,6 +425,8 @@ fn delete_secret(webview: Webview, key: String, cache: tauri::State<'_, SecretsC
     proposed.remove(&key);
     save_vault(&proposed)?;
     *secrets = proposed;
+    drop(secrets);
+    redact::refresh(&app);
     Ok(())
 }
 
@@ -397,20 +509,24 @@ fn desktop_log_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(logs_dir_path(app)?.join(DESKTOP_LOG_FILE))
 }
 
-fn append_desktop_log(app: &AppHandle, level: &str, message: &str) {
-    let Ok(path) = desktop_log_path(app) else {
-        return;
-    };
-
-    let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) else {
-        return;
-    };
+fn desktop_json_log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(logs_dir_path(app)?.join(DESKTOP_JSON_LOG_FILE))
+}
 
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
-    let _ = writeln!(file, "[{timestamp}][{level}] {message}");
+/// Write sidecar output to the shared rotating `local-api.log`.
+fn local_api_log_sink(app: &AppHandle) -> impl FnMut(&[u8]) + Send + 'static {
+    let app = app.clone();
+    move |line| {
+        let line = redact::redact_log(&String::from_utf8_lossy(line));
+        resource_limits::observe_output(&app, &line);
+        if let Some(sinks) = app.try_state::<LogSinks>() {
+            let mut log = sinks.local_api.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = log.write(line.as_bytes());
+        } else if let Ok(path) = sidecar_log_path(&app) {
+            logging::append_unrotated(&path, line.as_bytes());
+        }
+        log_tail::publish(&app, LogSource::LocalApi, line.as_bytes());
+    }
 }
 
 fn open_in_shell(arg: &str) -> Result<(), String> {
@@ -649,13 +765,20 @@ fn build_app_menu(handle: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
         true,
         Some("CmdOrCtrl+,"),
     )?;
+    let restart_local_api_item = MenuItem::with_id(
+        handle,
+        MENU_FILE_RESTART_LOCAL_API_ID,
+        "Restart Local API",
+        true,
+        None::<&str>,
+    )?;
     let separator = PredefinedMenuItem::separator(handle)?;
     let quit_item = PredefinedMenuItem::quit(handle, Some("Quit"))?;
     let file_menu = Submenu::with_items(
         handle,
         "File",
         true,
-        &[&settings_item, &separator, &quit_item],
+        &[&settings_item, &restart_local_api_item, &separator, &quit_item],
     )?;
 
     let about_metadata = AboutMetadata {
@@ -725,10 +848,18 @@ fn handle_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
     match event.id().as_ref() {
         MENU_FILE_SETTINGS_ID => {
             if let Err(err) = open_settings_window(app) {
-                append_desktop_log(app, "ERROR", &format!("settings menu failed: {err}"));
-                eprintln!("[tauri] settings menu failed: {err}");
+                log_event!(app, Error, format!("settings menu failed: {err}"));
             }
         }
+        MENU_FILE_RESTART_LOCAL_API_ID => {
+            // Stopping drains in-flight requests; keep that off the main thread.
+            let app = app.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = restart_local_api_impl(&app, false) {
+                    log_event!(&app, Error, format!("restart local API menu failed: {err}"));
+                }
+            });
+        }
         MENU_HELP_GITHUB_ID => {
             let _ = open_in_shell("https://github.com/koala73/worldmonitor");
         }
@@ -826,74 +957,53 @@ fn local_api_paths(app: &AppHandle) -> (PathBuf, PathBuf) {
     (sidecar_script, api_dir_root)
 }
 
-fn resolve_node_binary(app: &AppHandle) -> Option<PathBuf> {
-    if let Ok(explicit) = env::var("LOCAL_API_NODE_BIN") {
-        let explicit_path = PathBuf::from(explicit);
-        if explicit_path.is_file() {
-            return Some(explicit_path);
-        }
-        append_desktop_log(
-            app,
-            "WARN",
-            &format!(
-                "LOCAL_API_NODE_BIN is set but not a valid file: {}",
-                explicit_path.display()
-            ),
-        );
-    }
+fn loopback_port_available(port: u16) -> bool {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+}
 
-    if !cfg!(debug_assertions) {
-        let node_name = if cfg!(windows) { "node.exe" } else { "node" };
-        if let Ok(resource_dir) = app.path().resource_dir() {
-            let bundled = resource_dir.join("sidecar").join("node").join(node_name);
-            if bundled.is_file() {
-                return Some(bundled);
-            }
-        }
-    }
+fn local_api_port_in_use(port: u16) -> String {
+    format!(
+        "Local API port {port} is already in use by another process. \
+         Free it or change local_api.port in desktop-config.json"
+    )
+}
 
-    let node_name = if cfg!(windows) { "node.exe" } else { "node" };
-    if let Some(path_var) = env::var_os("PATH") {
-        for dir in env::split_paths(&path_var) {
-            let candidate = dir.join(node_name);
-            if candidate.is_file() {
-                return Some(candidate);
-            }
-        }
+/// Port to hand the sidecar plus whether it must bind exactly that port.
+/// A pinned port that is already taken is an error up front; otherwise the
+/// default is preferred and 0 lets the sidecar take any free port atomically.
+fn choose_local_api_port(pinned: Option<u16>) -> Result<(u16, bool), String> {
+    match pinned {
+        Some(port) if loopback_port_available(port) => Ok((port, true)),
+        Some(port) => Err(local_api_port_in_use(port)),
+        None if loopback_port_available(DEFAULT_LOCAL_API_PORT) => Ok((DEFAULT_LOCAL_API_PORT, false)),
+        None => Ok((0, false)),
     }
-
-    let common_locations = if cfg!(windows) {
-        vec![
-            PathBuf::from(r"C:\Program Files\nodejs\node.exe"),
-            PathBuf::from(r"C:\Program Files (x86)\nodejs\node.exe"),
-        ]
-    } else {
-        vec![
-            PathBuf::from("/opt/homebrew/bin/node"),
-            PathBuf::from("/usr/local/bin/node"),
-            PathBuf::from("/usr/bin/node"),
-            PathBuf::from("/opt/local/bin/node"),
-        ]
-    };
-
-    common_locations.into_iter().find(|path| path.is_file())
 }
 
-fn read_port_file(path: &Path, timeout_ms: u64) -> Option<u16> {
-    let start = std::time::Instant::now();
-    let interval = std::time::Duration::from_millis(100);
-    let timeout = std::time::Duration::from_millis(timeout_ms);
-    while start.elapsed() < timeout {
-        if let Ok(contents) = fs::read_to_string(path) {
-            if let Ok(port) = contents.trim().parse::<u16>() {
-                if port > 0 {
-                    return Some(port);
-                }
+/// Remove a sidecar that failed its startup handshake so the supervisor does
+/// not mistake it for a crash, and describe how it ended. Its lock file goes
+/// too, so the next launch does not probe a dead (or reused) pid.
+fn discard_local_api_child(app: &AppHandle, state: &LocalApiState) -> String {
+    let child = state.child.lock().ok().and_then(|mut slot| slot.take());
+    sidecar_lock::clear(app);
+    if let Ok(mut started_at) = state.started_at.lock() {
+        *started_at = None;
+    }
+    if let Ok(mut socket_slot) = state.socket.lock() {
+        *socket_slot = None;
+    }
+    match child {
+        Some(mut child) => {
+            if let Ok(None) = child.try_wait() {
+                let _ = child.kill();
             }
+            child
+                .wait()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|e| format!("wait failed: {e}"))
         }
-        std::thread::sleep(interval);
+        None => "already reaped".to_string(),
     }
-    None
 }
 
 fn start_local_api(app: &AppHandle) -> Result<(), String> {
@@ -905,11 +1015,25 @@ fn start_local_api(app: &AppHandle) -> Result<(), String> {
     if slot.is_some() {
         return Ok(());
     }
+    if let Some(dir) = local_api_capture::replay_dir(app) {
+        drop(slot);
+        if local_api_capture::is_replaying(app) {
+            return Ok(());
+        }
+        let pinned = app
+            .try_state::<DesktopConfigState>()
+            .and_then(|c| c.snapshot().local_api.port);
+        let (port, _) = choose_local_api_port(pinned)?;
+        return local_api_capture::start_replay(app, &dir, port);
+    }
 
     // Clear port state for fresh start
     if let Ok(mut port_slot) = state.port.lock() {
         *port_slot = None;
     }
+    if let Ok(mut socket_slot) = state.socket.lock() {
+        *socket_slot = None;
+    }
 
     let (script, resource_root) = local_api_paths(app);
     if !script.exists() {
@@ -918,47 +1042,49 @@ fn start_local_api(app: &AppHandle) -> Result<(), String> {
             script.display()
         ));
     }
-    let node_binary = resolve_node_binary(app).ok_or_else(|| {
-        "Node.js executable not found. Install Node 18+ or set LOCAL_API_NODE_BIN".to_string()
-    })?;
-
-    let port_file = logs_dir_path(app)?.join("sidecar.port");
-    let _ = fs::remove_file(&port_file);
+    let runtime = js_runtime::selected_runtime(app);
+    let runtime_binary = js_runtime::resolve_runtime_binary(app, runtime)?;
+
+    let api_config = app
+        .try_state::<DesktopConfigState>()
+        .map(|c| c.snapshot().local_api)
+        .unwrap_or_default();
+    let socket_path = match api_config.transport {
+        LocalApiTransport::Socket => Some(local_api_transport::prepare_socket_path(app)?),
+        LocalApiTransport::Tcp => None,
+    };
+    let (requested_port, strict_port) = match socket_path {
+        Some(_) => (0, false),
+        None => choose_local_api_port(api_config.port)?,
+    };
 
     let log_path = sidecar_log_path(app)?;
-    let log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)
-        .map_err(|e| format!("Failed to open local API log {}: {e}", log_path.display()))?;
-    let log_file_err = log_file
-        .try_clone()
-        .map_err(|e| format!("Failed to clone local API log handle: {e}"))?;
-
-    append_desktop_log(
+
+    log_event!(
         app,
-        "INFO",
-        &format!(
-            "starting local API sidecar script={} resource_root={} log={}",
-            script.display(),
-            resource_root.display(),
-            log_path.display()
-        ),
+        Info,
+        "starting local API sidecar",
+        script = script.display().to_string(),
+        resource_root = resource_root.display().to_string(),
+        log = log_path.display().to_string()
     );
-    append_desktop_log(
+    log_event!(
         app,
-        "INFO",
-        &format!("resolved node binary={}", node_binary.display()),
+        Info,
+        "resolved sidecar runtime",
+        runtime = runtime,
+        binary = runtime_binary.display().to_string()
     );
-    append_desktop_log(
-        app,
-        "INFO",
-        &format!(
-            "local API sidecar preferred port={} port_file={}",
-            DEFAULT_LOCAL_API_PORT,
-            port_file.display()
+    match &socket_path {
+        Some(path) => log_event!(app, Info, "local API sidecar requested socket", socket = path),
+        None => log_event!(
+            app,
+            Info,
+            "local API sidecar requested port",
+            port = requested_port,
+            strict = strict_port
         ),
-    );
+    }
 
     // Generate a unique token for local API auth (prevents other local processes from accessing sidecar)
     let mut token_slot = state
@@ -970,32 +1096,105 @@ fn start_local_api(app: &AppHandle) -> Result<(), String> {
     }
     let local_api_token = token_slot.clone().unwrap();
     drop(token_slot);
+    redact::refresh(app);
 
-    let mut cmd = Command::new(&node_binary);
+    let mut cmd = Command::new(&runtime_binary);
     #[cfg(windows)]
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW — hide the node.exe console
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW — hide the runtime's console
                                     // Sanitize paths for Node.js on Windows: strip \\?\ UNC prefix and set
                                     // explicit working directory to avoid bare drive-letter CWD issues that
                                     // cause EISDIR errors in Node.js module resolution.
     let script_for_node = sanitize_path_for_node(&script);
     let resource_for_node = sanitize_path_for_node(&resource_root);
-    append_desktop_log(
+    log_event!(
         app,
-        "INFO",
-        &format!("node args: script={script_for_node} resource_dir={resource_for_node}"),
+        Info,
+        "sidecar args",
+        script = script_for_node,
+        resource_dir = resource_for_node
     );
     let data_dir = logs_dir_path(app)
         .map(|p| sanitize_path_for_node(&p))
         .unwrap_or_else(|_| resource_for_node.clone());
-    cmd.arg(&script_for_node)
-        .env("LOCAL_API_PORT", DEFAULT_LOCAL_API_PORT.to_string())
-        .env("LOCAL_API_PORT_FILE", &port_file)
+    let mut write_dirs = vec![data_dir.clone()];
+    if let Some(dir) = socket_path.as_deref().and_then(Path::parent) {
+        write_dirs.push(sanitize_path_for_node(dir));
+    }
+    let record_dir = local_api_capture::record_dir(app);
+    if let Some(dir) = &record_dir {
+        write_dirs.push(sanitize_path_for_node(dir));
+    }
+    let limits = &api_config.limits;
+    cmd.args(runtime.script_args(&script_for_node, &write_dirs, limits.max_heap_mb))
+        .env("LOCAL_API_PORT", requested_port.to_string())
+        .env("LOCAL_API_PORT_STRICT", strict_port.to_string())
+        .env("LOCAL_API_HANDSHAKE", "stdout")
+        .env("LOCAL_API_METRICS", "stdout")
         .env("LOCAL_API_RESOURCE_DIR", &resource_for_node)
         .env("LOCAL_API_DATA_DIR", &data_dir)
         .env("LOCAL_API_MODE", "tauri-sidecar")
         .env("LOCAL_API_TOKEN", &local_api_token)
-        .stdout(Stdio::from(log_file))
-        .stderr(Stdio::from(log_file_err));
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(path) = &socket_path {
+        cmd.env("LOCAL_API_SOCKET", sanitize_path_for_node(path));
+    }
+    match &record_dir {
+        Some(dir) => {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create fixture dir {}: {e}", dir.display()))?;
+            log_event!(app, Info, "recording local API fixtures", dir = dir.display().to_string());
+            cmd.env("LOCAL_API_RECORD_DIR", sanitize_path_for_node(dir));
+        }
+        None => {
+            cmd.env_remove("LOCAL_API_RECORD_DIR");
+        }
+    }
+    runtime.apply_env(&mut cmd, limits.max_heap_mb);
+    egress_proxy::apply_env(app, &mut cmd)?;
+    host_bridge::apply_env(app, &mut cmd);
+    cloud_fallback::apply_env(app, &mut cmd);
+    resource_limits::apply(&mut cmd, limits);
+    if cfg!(windows) && (limits.max_address_space_mb.is_some() || limits.max_cpu_seconds.is_some()) {
+        log_event!(app, Warn, "address-space and CPU limits are not supported on Windows; only the heap cap applies");
+    }
+    log_event!(
+        app,
+        Info,
+        "sidecar resource limits",
+        max_heap_mb = limits.max_heap_mb,
+        max_address_space_mb = limits.max_address_space_mb,
+        max_cpu_seconds = limits.max_cpu_seconds
+    );
+    let sandbox_status = if api_config.sandbox {
+        let mut sandbox_write_dirs: Vec<PathBuf> = logs_dir_path(app).into_iter().collect();
+        sandbox_write_dirs.extend(socket_path.as_deref().and_then(Path::parent).map(Path::to_path_buf));
+        sandbox_write_dirs.extend(record_dir.clone());
+        let status = sandbox::apply(
+            &mut cmd,
+            &SandboxPaths {
+                runtime_binary: runtime_binary.clone(),
+                resource_dir: resource_root.clone(),
+                script_dir: script.parent().map(Path::to_path_buf),
+                write_dirs: sandbox_write_dirs,
+            },
+        );
+        match &status {
+            SandboxStatus::Enforced { abi } => {
+                log_event!(app, Info, "sidecar sandboxed with Landlock", abi = abi);
+                if runtime == JsRuntime::Deno {
+                    // Deno's module cache defaults to the home dir, which the sandbox hides.
+                    cmd.env("DENO_DIR", format!("{data_dir}/deno-cache"));
+                }
+            }
+            other => log_event!(app, Warn, "sidecar sandbox requested but not applied; running unsandboxed", status = other),
+        }
+        status
+    } else {
+        SandboxStatus::Disabled
+    };
+    if let Ok(mut slot) = state.sandbox.lock() {
+        *slot = Some(sandbox_status);
+    }
     if let Some(parent) = script.parent() {
         cmd.current_dir(parent);
     }
@@ -1009,11 +1208,7 @@ fn start_local_api(app: &AppHandle) -> Result<(), String> {
             secret_count += 1;
         }
     }
-    append_desktop_log(
-        app,
-        "INFO",
-        &format!("injected {secret_count} keychain secrets into sidecar env"),
-    );
+    log_event!(app, Info, "injected keychain secrets into sidecar env", count = secret_count);
 
     // Inject build-time secrets (CI) with runtime env fallback (dev)
     if let Some(url) = option_env!("CONVEX_URL") {
@@ -1022,56 +1217,292 @@ fn start_local_api(app: &AppHandle) -> Result<(), String> {
         cmd.env("CONVEX_URL", url);
     }
 
-    let child = cmd
+    let mut enforced_limits = limits.clone();
+    if !runtime.enforces_heap_cap() {
+        enforced_limits.max_heap_mb = None;
+    }
+    resource_limits::reset(app, &enforced_limits);
+    let mut child = cmd
         .spawn()
         .map_err(|e| format!("Failed to launch local API: {e}"))?;
-    append_desktop_log(
-        app,
-        "INFO",
-        &format!("local API sidecar started pid={}", child.id()),
-    );
+    log_event!(app, Info, "local API sidecar started", pid = child.id());
+    if let Ok(mut spawned) = state.runtime.lock() {
+        *spawned = Some((runtime, runtime_binary.clone()));
+    }
+    sidecar_lock::record(app, child.id(), &script_for_node, &data_dir);
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Local API stdout not captured".to_string())?;
+    let metrics_app = app.clone();
+    let handshake_rx = sidecar_io::forward_stdout(stdout, local_api_log_sink(app), move |sample| {
+        traffic_har::record(&metrics_app, &sample);
+        cloud_fallback::observe(&metrics_app, &sample);
+        local_api_metrics::observe(&metrics_app, sample)
+    });
+    if let Some(stderr) = child.stderr.take() {
+        sidecar_io::forward_stderr(stderr, local_api_log_sink(app));
+    }
+    let _starting = StartingGuard::new(&state);
     *slot = Some(child);
     drop(slot);
+    if let Ok(mut started_at) = state.started_at.lock() {
+        *started_at = Some(Instant::now());
+    }
 
-    // Wait for sidecar to write confirmed port (up to 5s)
-    if let Some(confirmed_port) = read_port_file(&port_file, 5000) {
-        append_desktop_log(
-            app,
-            "INFO",
-            &format!("sidecar confirmed port={confirmed_port}"),
-        );
-        if let Ok(mut port_slot) = state.port.lock() {
-            *port_slot = Some(confirmed_port);
+    // The sidecar prints its bound port (or bind error) as its first stdout line.
+    let timeout = std::time::Duration::from_secs(LOCAL_API_HANDSHAKE_TIMEOUT_SECS);
+    let handshake = match handshake_rx.recv_timeout(timeout) {
+        Ok(handshake) => handshake,
+        Err(RecvTimeoutError::Disconnected) => {
+            let status = discard_local_api_child(app, &state);
+            return Err(format!(
+                "Local API exited before reporting its port (status={status}); see {}",
+                log_path.display()
+            ));
         }
-    } else {
-        append_desktop_log(
-            app,
-            "WARN",
-            "sidecar port file not found within timeout, using default",
-        );
-        if let Ok(mut port_slot) = state.port.lock() {
-            *port_slot = Some(DEFAULT_LOCAL_API_PORT);
+        Err(RecvTimeoutError::Timeout) => {
+            let status = discard_local_api_child(app, &state);
+            return Err(format!(
+                "Local API did not report its port within {LOCAL_API_HANDSHAKE_TIMEOUT_SECS}s (status={status})"
+            ));
         }
+    };
+
+    if let (Some(path), None) = (&socket_path, &handshake.error) {
+        if handshake.socket.is_none() {
+            discard_local_api_child(app, &state);
+            return Err("Local API ignored socket transport; update the sidecar".to_string());
+        }
+        log_event!(app, Info, "sidecar confirmed socket", socket = path);
+        if let Ok(mut socket_slot) = state.socket.lock() {
+            *socket_slot = Some(path.clone());
+        }
+        return Ok(());
     }
 
+    match (handshake.port, handshake.error) {
+        (Some(port), None) if port > 0 => {
+            log_event!(app, Info, "sidecar confirmed port", port = port);
+            if let Ok(mut port_slot) = state.port.lock() {
+                *port_slot = Some(port);
+            }
+            Ok(())
+        }
+        (_, Some(code)) if code == "EADDRINUSE" && socket_path.is_none() => {
+            discard_local_api_child(app, &state);
+            Err(local_api_port_in_use(requested_port))
+        }
+        (_, error) => {
+            discard_local_api_child(app, &state);
+            Err(format!(
+                "Local API failed to start: {}",
+                handshake
+                    .message
+                    .or(error)
+                    .unwrap_or_else(|| "invalid handshake".to_string())
+            ))
+        }
+    }
+}
+
+/// Ask the sidecar to exit on its own so in-flight handlers can finish their
+/// writes. Unix gets SIGTERM; Windows has no equivalent for console-less
+/// children, so use the authenticated shutdown endpoint instead.
+#[cfg(unix)]
+fn request_graceful_exit(child: &Child, _addr: Option<&LocalApiAddr>, _token: Option<&str>) -> Result<(), String> {
+    // SAFETY: plain kill(2) on a pid we spawned and have not yet reaped.
+    let rc = unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(format!("SIGTERM failed: {}", std::io::Error::last_os_error()))
+    }
+}
+
+#[cfg(not(unix))]
+fn request_graceful_exit(_child: &Child, addr: Option<&LocalApiAddr>, token: Option<&str>) -> Result<(), String> {
+    let addr = addr.ok_or_else(|| "sidecar address unknown".to_string())?;
+    let headers: Vec<(String, String)> = token
+        .map(|t| vec![("Authorization".to_string(), format!("Bearer {t}"))])
+        .unwrap_or_default();
+    let response = local_api_transport::send(
+        addr,
+        "POST",
+        "/api/local-shutdown",
+        &headers,
+        &[],
+        std::time::Duration::from_secs(1),
+    )
+    .map_err(|e| format!("shutdown request failed: {e}"))?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("shutdown request HTTP {}", response.status));
+    }
     Ok(())
 }
 
 fn stop_local_api(app: &AppHandle) {
+    local_api_capture::stop_replay(app);
     if let Ok(state) = app.try_state::<LocalApiState>().ok_or(()) {
-        if let Ok(mut slot) = state.child.lock() {
-            if let Some(mut child) = slot.take() {
-                let _ = child.kill();
-                append_desktop_log(app, "INFO", "local API sidecar stopped");
+        // Take the child out first so the supervisor never sees it exit.
+        let child = state.child.lock().ok().and_then(|mut slot| slot.take());
+        let addr = LocalApiAddr::current(&state);
+        if let Some(mut child) = child {
+            let token = state.token.lock().ok().and_then(|g| g.clone());
+            let grace_ms = app
+                .try_state::<DesktopConfigState>()
+                .map(|c| c.snapshot().local_api.shutdown_grace_ms)
+                .unwrap_or_else(|| config::LocalApiConfig::default().shutdown_grace_ms);
+            let pid = child.id();
+
+            let mut exit_status = None;
+            match request_graceful_exit(&child, addr.as_ref(), token.as_deref()) {
+                Ok(()) => {
+                    let deadline = Instant::now() + std::time::Duration::from_millis(grace_ms);
+                    while Instant::now() < deadline {
+                        match child.try_wait() {
+                            Ok(Some(status)) => {
+                                exit_status = Some(status);
+                                break;
+                            }
+                            Ok(None) => std::thread::sleep(std::time::Duration::from_millis(50)),
+                            Err(_) => break,
+                        }
+                    }
+                }
+                Err(err) => log_event!(app, Warn, "graceful stop of local API sidecar failed", pid = pid, error = err),
+            }
+
+            match exit_status {
+                Some(status) => log_event!(
+                    app,
+                    Info,
+                    "local API sidecar exited gracefully",
+                    pid = pid,
+                    status = status.to_string()
+                ),
+                None => {
+                    let _ = child.kill();
+                    let status = child
+                        .wait()
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|e| format!("wait failed: {e}"));
+                    log_event!(
+                        app,
+                        Warn,
+                        format!("local API sidecar did not exit within {grace_ms}ms; force-killed"),
+                        pid = pid,
+                        status = status
+                    );
+                }
             }
+            sidecar_lock::clear(app);
+        }
+        if let Ok(mut started_at) = state.started_at.lock() {
+            *started_at = None;
         }
         if let Ok(mut port_slot) = state.port.lock() {
             *port_slot = None;
         }
-        if let Ok(log_dir) = logs_dir_path(app) {
-            let _ = fs::remove_file(log_dir.join("sidecar.port"));
+        if let Ok(mut socket_slot) = state.socket.lock() {
+            *socket_slot = None;
+        }
+    }
+}
+
+/// Stop and respawn the sidecar, optionally with a fresh `LOCAL_API_TOKEN`,
+/// and announce the new endpoint.
+fn restart_local_api_impl(app: &AppHandle, rotate_token: bool) -> Result<LocalApiEndpoint, String> {
+    log_event!(app, Info, "restarting local API sidecar", rotate_token = rotate_token);
+    stop_local_api(app);
+
+    let state = app.state::<LocalApiState>();
+    if rotate_token {
+        let mut token_slot = state
+            .token
+            .lock()
+            .map_err(|_| "Failed to lock token slot".to_string())?;
+        *token_slot = None;
+    }
+    let started = start_local_api(app);
+    supervisor::resume_after_manual_restart(app);
+    if let Err(err) = started {
+        health::report(app, HealthState::Down, Some(err.clone()));
+        return Err(err);
+    }
+
+    let endpoint = announce_local_api_endpoint(app)?;
+    log_event!(app, Info, "local API sidecar restarted", base_url = &endpoint.base_url, port = endpoint.port);
+    Ok(endpoint)
+}
+
+/// Wait for a freshly spawned sidecar to report its address, then push the
+/// endpoint to trusted windows so their fetch shim reconnects without a reload.
+fn announce_local_api_endpoint(app: &AppHandle) -> Result<LocalApiEndpoint, String> {
+    let state = app.state::<LocalApiState>();
+    let deadline = Instant::now() + std::time::Duration::from_secs(10);
+    let addr = loop {
+        if let Some(addr) = LocalApiAddr::current(&state) {
+            break addr;
+        }
+        if Instant::now() >= deadline {
+            return Err("Local API restarted but did not report its address within 10s".to_string());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    };
+    let token = state
+        .token
+        .lock()
+        .map_err(|_| "Failed to lock local API token".to_string())?
+        .clone()
+        .ok_or_else(|| "Token not generated".to_string())?;
+
+    let port = match addr {
+        LocalApiAddr::Tcp(port) => Some(port),
+        LocalApiAddr::Socket(_) => None,
+    };
+    let endpoint = LocalApiEndpoint {
+        port,
+        base_url: local_api_transport::base_url(&addr),
+        token,
+    };
+    // The payload carries the token, so never broadcast it to untrusted windows.
+    for label in TRUSTED_WINDOWS {
+        if app.get_webview_window(label).is_some() {
+            let _ = app.emit_to(label, LOCAL_API_RESTARTED_EVENT, endpoint.clone());
         }
     }
+    Ok(endpoint)
+}
+
+#[tauri::command]
+async fn restart_local_api(
+    webview: Webview,
+    app: AppHandle,
+    rotate_token: Option<bool>,
+) -> Result<LocalApiEndpoint, String> {
+    require_trusted_window(webview.label())?;
+    let rotate_token = rotate_token.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || restart_local_api_impl(&app, rotate_token))
+        .await
+        .map_err(|e| format!("Restart task failed: {e}"))?
+}
+
+/// Switch between live, recording and replay, then restart the local API so
+/// the new mode applies. `dir` is relative to `fixtures/` in app data and
+/// defaults to `local-api`.
+#[tauri::command]
+async fn set_local_api_capture_mode(
+    webview: Webview,
+    app: AppHandle,
+    mode: String,
+    dir: Option<String>,
+) -> Result<LocalApiEndpoint, String> {
+    require_trusted_window(webview.label())?;
+    local_api_capture::set_mode(&app, &mode, dir)?;
+    tauri::async_runtime::spawn_blocking(move || restart_local_api_impl(&app, false))
+        .await
+        .map_err(|e| format!("Restart task failed: {e}"))?
 }
 
 #[cfg(target_os = "linux")]
@@ -1119,6 +1550,12 @@ fn resolve_appimage_gio_module_dir() -> Option<PathBuf> {
 }
 
 fn main() {
+    crash::install_panic_hook(env!("CARGO_PKG_VERSION"));
+    let capture_mode = local_api_capture::mode_from_args(env::args()).unwrap_or_else(|err| {
+        eprintln!("[tauri] ignoring capture flags: {err}");
+        CaptureMode::Off
+    });
+
     // Work around WebKitGTK rendering issues on Linux that can cause blank white
     // screens. DMA-BUF renderer failures are common with NVIDIA drivers and on
     // immutable distros (e.g. Bazzite/Fedora Atomic).  Setting the env var before
@@ -1255,9 +1692,32 @@ fn main() {
 
     tauri::Builder::default()
         .menu(build_app_menu)
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
         .on_menu_event(handle_menu_event)
         .manage(LocalApiState::default())
         .manage(SecretsCache::load_from_keychain())
+        .manage(SupervisorState::default())
+        .manage(HealthMonitor::default())
+        .manage(LogStreams::default())
+        .manage(LimitMonitor::default())
+        .manage(EgressProxy::default())
+        .manage(HostBridge::default())
+        .manage(LocalApiMetrics::default())
+        .manage(LocalApiCapture::new(capture_mode))
+        .manage(TrafficLog::default())
+        .manage(CloudFallbackStats::default())
+        .register_asynchronous_uri_scheme_protocol(
+            local_api_transport::LOCAL_API_SCHEME,
+            |ctx, request, responder| {
+                let app = ctx.app_handle().clone();
+                let label = ctx.webview_label().to_string();
+                // Sidecar calls can take seconds; keep them off the webview thread.
+                std::thread::spawn(move || {
+                    responder.respond(local_api_transport::bridge_request(&app, &label, request));
+                });
+            },
+        )
         .invoke_handler(tauri::generate_handler![
             list_supported_secret_keys,
             get_secret,
@@ -1266,6 +1726,8 @@ fn main() {
             delete_secret,
             get_local_api_token,
             get_local_api_port,
+            get_local_api_base_url,
+            restart_local_api,
             get_desktop_runtime_info,
             read_cache_entry,
             write_cache_entry,
@@ -1278,21 +1740,72 @@ fn main() {
             close_live_channels_window,
             open_url,
             open_youtube_login,
-            fetch_polymarket
+            fetch_polymarket,
+            retention::get_retention_policy,
+            retention::set_retention_policy,
+            retention::apply_retention_now,
+            logging::get_log_level,
+            logging::set_log_level,
+            log_tail::tail_log,
+            log_tail::subscribe_log_stream,
+            log_tail::unsubscribe_log_stream,
+            diagnostics::export_diagnostics_bundle,
+            egress_proxy::get_egress_report,
+            local_api_metrics::get_local_api_metrics,
+            local_api_capture::get_local_api_capture_mode,
+            set_local_api_capture_mode,
+            traffic_har::export_local_api_har,
+            local_api_routes::get_local_api_routes,
+            cloud_fallback::get_cloud_fallback_report,
+            cloud_fallback::set_cloud_fallback_policy,
+            js_runtime::get_js_runtime_candidates,
+            crash::get_previous_crash,
+            crash::open_crash_report,
+            crash::dismiss_previous_crash,
+            health::get_local_api_status,
+            supervisor::get_local_api_supervisor_status
         ])
         .setup(|app| {
             // Load persistent cache into memory (avoids 14MB file I/O on every IPC call)
             let cache_path = cache_file_path(&app.handle()).unwrap_or_default();
             app.manage(PersistentCache::load(&cache_path));
-
-            if let Err(err) = start_local_api(&app.handle()) {
-                append_desktop_log(
-                    &app.handle(),
-                    "ERROR",
-                    &format!("local API sidecar failed to start: {err}"),
-                );
-                eprintln!("[tauri] local API sidecar failed to start: {err}");
+            let config_path = config::config_file_path(app.handle()).unwrap_or_default();
+            app.manage(DesktopConfigState::load(&config_path));
+            crash::init(app.handle());
+            let desktop_config = app.state::<DesktopConfigState>().snapshot();
+            app.manage(Logger::new(&desktop_config.logging));
+            redact::refresh(app.handle());
+            match LogSinks::new(app.handle(), &desktop_config.log_rotation) {
+                Ok(sinks) => {
+                    app.manage(sinks);
+                }
+                Err(err) => log_event!(app.handle(), Warn, "log rotation unavailable", error = err),
+            }
+            retention::spawn_retention_task(app.handle().clone());
+            if let Err(err) = egress_proxy::start(app.handle()) {
+                // The sidecar refuses to start without it, so egress stays closed.
+                log_event!(app.handle(), Error, "egress proxy failed to start", error = err);
+            }
+            if let Err(err) = host_bridge::start(app.handle()) {
+                // The sidecar still starts; its host calls fail as unavailable.
+                log_event!(app.handle(), Error, "host bridge failed to start", error = err);
             }
+            if let Err(err) = local_api_metrics::start_prometheus_endpoint(app.handle()) {
+                log_event!(app.handle(), Error, "prometheus metrics endpoint failed to start", error = err);
+            }
+
+            // Spawn the sidecar off the setup path so the window shows immediately;
+            // readiness is reported through the `local-api-status` event.
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                sidecar_lock::reap_orphan(&handle);
+                if let Err(err) = start_local_api(&handle) {
+                    log_event!(&handle, Error, format!("local API sidecar failed to start: {err}"));
+                    health::report(&handle, HealthState::Down, Some(err));
+                }
+            });
+            health::spawn_health_monitor(app.handle().clone());
+            supervisor::spawn_supervisor(app.handle().clone());
 
             Ok(())
         })
@@ -1341,7 +1854,13 @@ fn main() {
                             let _ = cache.flush(&path);
                         }
                     }
+                    if let Some(supervisor) = app.try_state::<SupervisorState>() {
+                        supervisor.shutdown(app);
+                    }
                     stop_local_api(app);
+                    if matches!(event, RunEvent::Exit) {
+                        crash::settle_clean_exit();
+                    }
                 }
                 _ => {}
             }
@@ -0,0 +1,210 @@
+use std::sync::{OnceLock, RwLock, TryLockError};
+
+use regex::Regex;
+use tauri::{AppHandle, Manager};
+
+use crate::egress_proxy::EgressProxy;
+use crate::host_bridge::HostBridge;
+use crate::{LocalApiState, SecretsCache};
+
+pub const REDACTED: &str = "[REDACTED]";
+/// Shorter values (flags like "1", "true") would mask unrelated text.
+const MIN_SECRET_LEN: usize = 6;
+
+/// api_key=..., "access_token": "...", password: ..., GROQ_API_KEY=... The key
+/// may follow `_`, so env-style names match, but not a letter or digit.
+fn key_value_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r#"(?i)((?:^|[^A-Za-z0-9])(?:api[_-]?key|access[_-]?token|refresh[_-]?token|auth[_-]?token|client[_-]?secret|secret|password|passwd|token)["']?\s*[=:]\s*["']?)([^\s"'&,;)}\]]+)"#,
+        )
+        .expect("valid redaction pattern")
+    })
+}
+
+/// Token shapes worth masking even when the value is not one we stored:
+/// a handler may log a key it received from a request or an upstream error.
+/// Replacements keep the capture group so the line still says what was there.
+fn patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // Short query parameter names that are only secret in a query string.
+            (r#"([?&](?:key|sig|signature|auth)=)[^&\s"']+"#, "${1}[REDACTED]"),
+            (r#"(?i)(authorization["']?\s*[=:]\s*["']?basic\s+)[^\s"',;]+"#, "${1}[REDACTED]"),
+            (r"(?i)\b(bearer\s+)[A-Za-z0-9\-._~+/]{8,}=*", "${1}[REDACTED]"),
+            (r"\bsk-[A-Za-z0-9_\-]{16,}", REDACTED),
+            (r"\bgh[pousr]_[A-Za-z0-9]{20,}", REDACTED),
+            (r"\bAKIA[0-9A-Z]{16}\b", REDACTED),
+            (r"\bAIza[0-9A-Za-z_\-]{35}", REDACTED),
+            (r"\bxox[abprs]-[A-Za-z0-9\-]{10,}", REDACTED),
+            (r"\beyJ[A-Za-z0-9_\-]{8,}\.[A-Za-z0-9_\-]{8,}\.[A-Za-z0-9_\-]{8,}", REDACTED),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("valid redaction pattern"), replacement))
+        .collect()
+    })
+}
+
+/// Masks every known secret value and common token patterns in free text. Built from the live
+/// keychain cache and the sidecar token, so it reflects edits made since launch.
+pub struct Redactor {
+    values: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new(values: impl IntoIterator<Item = String>) -> Self {
+        let mut values: Vec<String> = values
+            .into_iter()
+            .map(|v| v.trim().to_string())
+            .filter(|v| v.len() >= MIN_SECRET_LEN)
+            .collect();
+        // Longest first, so a secret that contains another is masked whole.
+        values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        values.dedup();
+        Redactor { values }
+    }
+
+    pub fn from_app(app: &AppHandle) -> Self {
+        let mut values = Vec::new();
+        if let Some(cache) = app.try_state::<SecretsCache>() {
+            if let Ok(secrets) = cache.secrets.lock() {
+                values.extend(secrets.values().cloned());
+            }
+        }
+        if let Some(state) = app.try_state::<LocalApiState>() {
+            if let Some(token) = state.token.lock().ok().and_then(|g| g.clone()) {
+                values.push(token);
+            }
+        }
+        if let Some(token) = app.try_state::<EgressProxy>().and_then(|p| p.token()) {
+            values.push(token);
+        }
+        if let Some(token) = app.try_state::<HostBridge>().and_then(|b| b.token()) {
+            values.push(token);
+        }
+        Redactor::new(values)
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for value in &self.values {
+            if out.contains(value.as_str()) {
+                out = out.replace(value.as_str(), REDACTED);
+            }
+        }
+        let masked = key_value_pattern().replace_all(&out, |caps: &regex::Captures| {
+            let value = &caps[2];
+            // JSON literals and flags say nothing about a secret.
+            if ["true", "false", "null"].iter().any(|v| value.eq_ignore_ascii_case(v)) {
+                caps[0].to_string()
+            } else {
+                format!("{}{REDACTED}", &caps[1])
+            }
+        });
+        if let std::borrow::Cow::Owned(replaced) = masked {
+            out = replaced;
+        }
+        for (pattern, replacement) in patterns() {
+            if let std::borrow::Cow::Owned(replaced) = pattern.replace_all(&out, *replacement) {
+                out = replaced;
+            }
+        }
+        out
+    }
+}
+
+/// The redactor the log writers and the panic hook use. Rebuilt by
+/// [`refresh`] whenever a secret or the sidecar token changes, so writing a
+/// line never takes those locks. Process-global so the panic hook, which has
+/// no `AppHandle`, sees the same values.
+fn snapshot() -> &'static RwLock<Redactor> {
+    static SNAPSHOT: OnceLock<RwLock<Redactor>> = OnceLock::new();
+    SNAPSHOT.get_or_init(|| RwLock::new(Redactor::new(Vec::new())))
+}
+
+pub(crate) fn store(redactor: Redactor) {
+    *snapshot().write().unwrap_or_else(|e| e.into_inner()) = redactor;
+}
+
+pub fn refresh(app: &AppHandle) {
+    store(Redactor::from_app(app));
+}
+
+/// Mask secrets in a line about to be written to a log file or stream.
+pub fn redact_log(text: &str) -> String {
+    snapshot().read().unwrap_or_else(|e| e.into_inner()).redact(text)
+}
+
+/// [`redact_log`] for the panic hook: never waits for the snapshot. If it is
+/// being rebuilt, possibly by the panicking thread, only patterns are masked.
+pub fn try_redact_log(text: &str) -> String {
+    match snapshot().try_read() {
+        Ok(redactor) => redactor.redact(text),
+        Err(TryLockError::Poisoned(e)) => e.into_inner().redact(text),
+        Err(TryLockError::WouldBlock) => Redactor::new(Vec::new()).redact(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_known_values_longest_first() {
+        let redactor = Redactor::new(vec!["abc123".to_string(), "abc123-extended".to_string(), "1".to_string()]);
+        assert_eq!(
+            redactor.redact("key=abc123-extended other=abc123 flag=1"),
+            "key=[REDACTED] other=[REDACTED] flag=1"
+        );
+    }
+
+    #[test]
+    fn masks_common_token_patterns() {
+        let redactor = Redactor::new(Vec::new());
+        assert_eq!(
+            redactor.redact("GET /api/x?q=iran&api_key=abc123xyz&page=2 200"),
+            "GET /api/x?q=iran&api_key=[REDACTED]&page=2 200"
+        );
+        assert_eq!(redactor.redact("upstream /v1?key=AbCdEf01"), "upstream /v1?key=[REDACTED]");
+        assert_eq!(
+            redactor.redact(r#"{"token":"s3cr3t-value","count":3}"#),
+            r#"{"token":"[REDACTED]","count":3}"#
+        );
+        assert_eq!(
+            redactor.redact("Authorization: Bearer abcdefghijklmnop"),
+            "Authorization: Bearer [REDACTED]"
+        );
+        assert_eq!(
+            redactor.redact("using sk-ant-REDACTED and ghp_abcdefghijklmnopqrstuv"),
+            "using [REDACTED] and [REDACTED]"
+        );
+        let plain = "basic setup done, 3 tokens cached, keyboard=us";
+        assert_eq!(redactor.redact(plain), plain);
+    }
+
+    #[test]
+    fn leaves_similar_keys_and_literals_alone() {
+        let redactor = Redactor::new(Vec::new());
+        for plain in [
+            "rotate_token=false",
+            r#"{"rotate_token":false,"mytoken":"abc123xyz"}"#,
+            r#"{"token":null,"password":true}"#,
+        ] {
+            assert_eq!(redactor.redact(plain), plain);
+        }
+        assert_eq!(redactor.redact("password=hunter22 secret: FALSEHOOD"), "password=[REDACTED] secret: [REDACTED]");
+    }
+
+    #[test]
+    fn masks_env_style_assignments() {
+        let redactor = Redactor::new(Vec::new());
+        assert_eq!(redactor.redact("GROQ_API_KEY=abcdef123456"), "GROQ_API_KEY=[REDACTED]");
+        assert_eq!(
+            redactor.redact("env FRED_API_KEY=fred98765 X_AUTH_TOKEN=xyz12345 done"),
+            "env FRED_API_KEY=[REDACTED] X_AUTH_TOKEN=[REDACTED] done"
+        );
+        assert_eq!(redactor.redact("rotate_token=false"), "rotate_token=false");
+    }
+}
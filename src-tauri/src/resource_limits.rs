@@ -0,0 +1,195 @@
+use std::process::{Command, ExitStatus};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+/// Caps applied to the sidecar when it is spawned. All opt-in: unset fields
+/// leave the runtime's own defaults in place.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// JavaScript heap cap. Node and Deno get `--max-old-space-size`. Bun has
+    /// no enforced cap; it gets `BUN_JSC_forceRAMSize` as a GC sizing hint.
+    pub max_heap_mb: Option<u64>,
+    /// `RLIMIT_AS` on Unix. Runtimes reserve far more address space than they
+    /// use, so keep this well above `max_heap_mb` (several GiB).
+    pub max_address_space_mb: Option<u64>,
+    /// `RLIMIT_CPU` on Unix: total CPU seconds before the kernel sends SIGXCPU.
+    pub max_cpu_seconds: Option<u64>,
+}
+
+/// Seconds between the soft CPU limit (SIGXCPU) and the hard one (SIGKILL).
+const CPU_HARD_LIMIT_SLACK_SECS: u64 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LimitBreach {
+    Heap,
+    AddressSpace,
+    Cpu,
+}
+
+/// What the sidecar said on its way out, so the supervisor can tell a
+/// limit kill from an ordinary crash once the exit status is known.
+#[derive(Default)]
+pub struct LimitMonitor {
+    seen: Mutex<Option<LimitBreach>>,
+    /// Limits the current child was spawned with.
+    active: Mutex<Option<ResourceLimits>>,
+}
+
+/// Set the rlimits in the child between fork and exec.
+#[cfg(unix)]
+pub fn apply(cmd: &mut Command, limits: &ResourceLimits) {
+    use std::os::unix::process::CommandExt;
+
+    let address_space = limits.max_address_space_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+    let cpu = limits.max_cpu_seconds;
+    if address_space.is_none() && cpu.is_none() {
+        return;
+    }
+    let set = |resource, soft: u64, hard: u64| {
+        let limit = libc::rlimit {
+            rlim_cur: soft as libc::rlim_t,
+            rlim_max: hard as libc::rlim_t,
+        };
+        // SAFETY: setrlimit is async-signal-safe and only touches the child.
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    };
+    // SAFETY: the closure only calls setrlimit, which is safe after fork.
+    unsafe {
+        cmd.pre_exec(move || {
+            if let Some(bytes) = address_space {
+                set(libc::RLIMIT_AS, bytes, bytes)?;
+            }
+            if let Some(secs) = cpu {
+                set(libc::RLIMIT_CPU, secs, secs + CPU_HARD_LIMIT_SLACK_SECS)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply(_cmd: &mut Command, _limits: &ResourceLimits) {}
+
+/// Which limit a fatal runtime message points at, if any.
+fn breach_in_line(line: &str) -> Option<LimitBreach> {
+    if line.contains("Reached heap limit") || line.contains("JavaScript heap out of memory") {
+        Some(LimitBreach::Heap)
+    } else if line.contains("Fatal process out of memory") || line.contains("Fatal JavaScript out of memory") {
+        Some(LimitBreach::AddressSpace)
+    } else {
+        None
+    }
+}
+
+/// Called for every line of sidecar output.
+pub fn observe_output(app: &AppHandle, line: &str) {
+    let Some(breach) = breach_in_line(line) else {
+        return;
+    };
+    if let Some(monitor) = app.try_state::<LimitMonitor>() {
+        let mut seen = monitor.seen.lock().unwrap_or_else(|e| e.into_inner());
+        // The heap message comes first and is the more specific one.
+        seen.get_or_insert(breach);
+    }
+}
+
+/// Forget output from a previous child and remember the limits the next
+/// one is spawned with.
+pub fn reset(app: &AppHandle, limits: &ResourceLimits) {
+    if let Some(monitor) = app.try_state::<LimitMonitor>() {
+        *monitor.seen.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        *monitor.active.lock().unwrap_or_else(|e| e.into_inner()) = Some(limits.clone());
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Only limits that were set count: the runtime prints the same
+/// out-of-memory messages when it runs out on its own.
+fn classify(seen: Option<LimitBreach>, signal: Option<i32>, limits: &ResourceLimits) -> Option<LimitBreach> {
+    #[cfg(unix)]
+    if signal == Some(libc::SIGXCPU) && limits.max_cpu_seconds.is_some() {
+        return Some(LimitBreach::Cpu);
+    }
+    #[cfg(not(unix))]
+    let _ = signal;
+    seen.filter(|breach| match breach {
+        LimitBreach::Heap => limits.max_heap_mb.is_some(),
+        LimitBreach::AddressSpace => cfg!(unix) && limits.max_address_space_mb.is_some(),
+        LimitBreach::Cpu => limits.max_cpu_seconds.is_some(),
+    })
+}
+
+/// Whether the child that just exited was killed by one of the limits it
+/// was spawned with.
+pub fn take_breach(app: &AppHandle, status: Option<&ExitStatus>) -> Option<LimitBreach> {
+    let monitor = app.try_state::<LimitMonitor>()?;
+    let seen = monitor.seen.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let limits = monitor.active.lock().unwrap_or_else(|e| e.into_inner()).clone()?;
+    classify(seen, status.and_then(exit_signal), &limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_fatal_memory_messages() {
+        assert_eq!(
+            breach_in_line("FATAL ERROR: Reached heap limit Allocation failed - JavaScript heap out of memory"),
+            Some(LimitBreach::Heap)
+        );
+        assert_eq!(
+            breach_in_line("# Fatal process out of memory: Failed to reserve virtual memory for CodeRange"),
+            Some(LimitBreach::AddressSpace)
+        );
+        assert_eq!(breach_in_line("[local-api] GET /api/news 200"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sigxcpu_means_cpu_limit() {
+        let limits = ResourceLimits {
+            max_heap_mb: Some(512),
+            max_address_space_mb: None,
+            max_cpu_seconds: Some(600),
+        };
+        assert_eq!(classify(None, Some(libc::SIGXCPU), &limits), Some(LimitBreach::Cpu));
+        assert_eq!(classify(Some(LimitBreach::Heap), Some(libc::SIGABRT), &limits), Some(LimitBreach::Heap));
+        assert_eq!(classify(None, Some(libc::SIGSEGV), &limits), None);
+    }
+
+    #[test]
+    fn ignores_breaches_of_limits_that_were_not_set() {
+        let unlimited = ResourceLimits {
+            max_heap_mb: None,
+            max_address_space_mb: None,
+            max_cpu_seconds: None,
+        };
+        assert_eq!(classify(Some(LimitBreach::Heap), None, &unlimited), None);
+        assert_eq!(classify(Some(LimitBreach::AddressSpace), None, &unlimited), None);
+        #[cfg(unix)]
+        assert_eq!(classify(None, Some(libc::SIGXCPU), &unlimited), None);
+
+        // No cap is set by default, so a default install never reports a breach.
+        let defaults = ResourceLimits::default();
+        assert_eq!(classify(Some(LimitBreach::Heap), None, &defaults), None);
+        assert_eq!(classify(Some(LimitBreach::AddressSpace), None, &defaults), None);
+    }
+}
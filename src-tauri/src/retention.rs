@@ -0,0 +1,286 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::config::DesktopConfigState;
+use crate::logging::now_ms;
+use crate::{
+    cache_file_path, logs_dir_path, require_trusted_window, PersistentCache,
+    DESKTOP_JSON_LOG_FILE, DESKTOP_LOG_FILE, LOCAL_API_LOG_FILE,
+};
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+/// Let startup I/O (cache load, sidecar spawn) settle before the first sweep.
+const RETENTION_INITIAL_DELAY: Duration = Duration::from_secs(60);
+
+/// Max age for persistent-cache entries whose key starts with `prefix`.
+/// Keys follow the frontend's `<namespace>:<id>` convention (`feed:`,
+/// `breaker:`, `api-response:` ...).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CacheRetentionRule {
+    pub prefix: String,
+    pub max_age_days: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub cache: Vec<CacheRetentionRule>,
+    /// Files in the log dir untouched for longer than this are deleted.
+    /// `None` keeps logs forever.
+    pub logs_max_age_days: Option<u64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            enabled: true,
+            interval_minutes: 60,
+            cache: vec![
+                CacheRetentionRule {
+                    prefix: "feed:".to_string(),
+                    max_age_days: 7,
+                },
+                CacheRetentionRule {
+                    prefix: "api-response:".to_string(),
+                    max_age_days: 7,
+                },
+                CacheRetentionRule {
+                    prefix: "breaker:".to_string(),
+                    max_age_days: 30,
+                },
+            ],
+            logs_max_age_days: Some(14),
+        }
+    }
+}
+
+#[derive(Default, Serialize)]
+pub struct RetentionReport {
+    pub cache_keys: Vec<String>,
+    pub log_files: Vec<String>,
+    pub log_bytes: u64,
+}
+
+/// Keys whose envelope `updatedAt` (epoch ms) is older than the longest
+/// matching rule allows. Entries without a timestamp or matching rule are kept.
+fn expired_cache_keys(data: &Map<String, Value>, rules: &[CacheRetentionRule], now_ms: u64) -> Vec<String> {
+    data.iter()
+        .filter_map(|(key, value)| {
+            let rule = rules
+                .iter()
+                .filter(|r| r.max_age_days > 0 && key.starts_with(&r.prefix))
+                .max_by_key(|r| r.prefix.len())?;
+            let updated_at = value.get("updatedAt")?.as_f64()? as u64;
+            let max_age_ms = rule.max_age_days.saturating_mul(DAY_MS);
+            (now_ms.saturating_sub(updated_at) > max_age_ms).then(|| key.clone())
+        })
+        .collect()
+}
+
+fn purge_cache(app: &AppHandle, rules: &[CacheRetentionRule], now_ms: u64) -> Result<Vec<String>, String> {
+    let Some(cache) = app.try_state::<PersistentCache>() else {
+        return Ok(Vec::new());
+    };
+
+    let removed = {
+        // Hold the write lock so a concurrent write_cache_entry flush cannot
+        // resurrect keys we are about to drop.
+        let _write_guard = cache.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut data = cache.data.lock().unwrap_or_else(|e| e.into_inner());
+        let expired = expired_cache_keys(&data, rules, now_ms);
+        for key in &expired {
+            data.remove(key);
+        }
+        if !expired.is_empty() {
+            *cache.dirty.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        }
+        expired
+    };
+
+    if !removed.is_empty() {
+        cache.flush(&cache_file_path(app)?)?;
+    }
+    Ok(removed)
+}
+
+/// Rotated generations (`desktop.log.2`) and crash reports. The log dir is
+/// also the sidecar's `LOCAL_API_DATA_DIR`, so any other file there may be
+/// handler state such as `verbose-mode.json` and is left alone.
+fn is_log_artifact(name: &str) -> bool {
+    let rotated = [DESKTOP_LOG_FILE, DESKTOP_JSON_LOG_FILE, LOCAL_API_LOG_FILE].iter().any(|base| {
+        name.strip_prefix(base)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .is_some_and(|generation| !generation.is_empty() && generation.bytes().all(|b| b.is_ascii_digit()))
+    });
+    rotated || (name.starts_with("crash-") && name.ends_with(".json"))
+}
+
+fn purge_logs(dir: &Path, max_age_days: u64, report: &mut RetentionReport) -> Result<(), String> {
+    let max_age = Duration::from_secs(max_age_days.saturating_mul(DAY_MS / 1000));
+    let now = SystemTime::now();
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read log dir {}: {e}", dir.display()))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !is_log_artifact(&name) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else { continue };
+        if !meta.is_file() {
+            continue;
+        }
+        let Ok(modified) = meta.modified() else { continue };
+        let age = now.duration_since(modified).unwrap_or_default();
+        if age > max_age && fs::remove_file(entry.path()).is_ok() {
+            report.log_bytes += meta.len();
+            report.log_files.push(name);
+        }
+    }
+    Ok(())
+}
+
+pub fn apply_retention(app: &AppHandle) -> Result<RetentionReport, String> {
+    let policy = app.state::<DesktopConfigState>().snapshot().retention;
+    let mut report = RetentionReport {
+        cache_keys: purge_cache(app, &policy.cache, now_ms())?,
+        ..Default::default()
+    };
+    if let Some(days) = policy.logs_max_age_days.filter(|d| *d > 0) {
+        purge_logs(&logs_dir_path(app)?, days, &mut report)?;
+    }
+
+    if !report.cache_keys.is_empty() || !report.log_files.is_empty() {
+        log_event!(
+            app,
+            Info,
+            "retention purged",
+            cache_keys = report.cache_keys.len(),
+            log_files = report.log_files.len(),
+            log_bytes = report.log_bytes
+        );
+    }
+    Ok(report)
+}
+
+/// Background sweep. Re-reads the policy every cycle so edits made through
+/// `set_retention_policy` apply without a restart.
+pub fn spawn_retention_task(app: AppHandle) {
+    std::thread::spawn(move || {
+        std::thread::sleep(RETENTION_INITIAL_DELAY);
+        loop {
+            let policy = app.state::<DesktopConfigState>().snapshot().retention;
+            if policy.enabled {
+                if let Err(err) = apply_retention(&app) {
+                    log_event!(&app, Warn, "retention sweep failed", error = err);
+                }
+            }
+            let minutes = policy.interval_minutes.max(1);
+            std::thread::sleep(Duration::from_secs(minutes * 60));
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_retention_policy(webview: Webview, config: tauri::State<'_, DesktopConfigState>) -> Result<RetentionPolicy, String> {
+    require_trusted_window(webview.label())?;
+    Ok(config.snapshot().retention)
+}
+
+#[tauri::command]
+pub fn set_retention_policy(
+    webview: Webview,
+    app: AppHandle,
+    config: tauri::State<'_, DesktopConfigState>,
+    policy: RetentionPolicy,
+) -> Result<RetentionPolicy, String> {
+    require_trusted_window(webview.label())?;
+    config
+        .update(&app, |c| c.retention = policy)
+        .map(|c| c.retention)
+}
+
+#[tauri::command]
+pub async fn apply_retention_now(webview: Webview, app: AppHandle) -> Result<RetentionReport, String> {
+    require_trusted_window(webview.label())?;
+    tauri::async_runtime::spawn_blocking(move || apply_retention(&app))
+        .await
+        .map_err(|e| format!("Retention task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules() -> Vec<CacheRetentionRule> {
+        vec![
+            CacheRetentionRule { prefix: "feed:".into(), max_age_days: 7 },
+            CacheRetentionRule { prefix: "feed:markets".into(), max_age_days: 30 },
+        ]
+    }
+
+    #[test]
+    fn expires_entries_past_their_namespace_age() {
+        let now = 100 * DAY_MS;
+        let data = json!({
+            "feed:world": { "updatedAt": now - 8 * DAY_MS, "data": [] },
+            "feed:tech": { "updatedAt": now - DAY_MS, "data": [] },
+        });
+        let expired = expired_cache_keys(data.as_object().unwrap(), &rules(), now);
+        assert_eq!(expired, vec!["feed:world".to_string()]);
+    }
+
+    #[test]
+    fn longest_matching_prefix_wins() {
+        let now = 100 * DAY_MS;
+        let data = json!({ "feed:markets-us": { "updatedAt": now - 8 * DAY_MS } });
+        assert!(expired_cache_keys(data.as_object().unwrap(), &rules(), now).is_empty());
+    }
+
+    #[test]
+    fn keeps_unmatched_and_untimestamped_entries() {
+        let now = 100 * DAY_MS;
+        let data = json!({
+            "summary:world-brief": { "updatedAt": 0 },
+            "feed:legacy": { "data": [] },
+        });
+        assert!(expired_cache_keys(data.as_object().unwrap(), &rules(), now).is_empty());
+    }
+
+    #[test]
+    fn purges_only_old_log_artifacts() {
+        let dir = std::env::temp_dir().join(format!("wm-retention-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let names = [
+            DESKTOP_LOG_FILE,
+            "desktop.log.1",
+            "local-api.log.3",
+            "crash-20260101-000000-42.json",
+            "verbose-mode.json",
+            "local-api.log.bak",
+        ];
+        let two_days_ago = SystemTime::now() - Duration::from_secs(2 * DAY_MS / 1000);
+        for name in names {
+            let path = dir.join(name);
+            fs::write(&path, b"x").unwrap();
+            fs::File::options().write(true).open(&path).unwrap().set_modified(two_days_ago).unwrap();
+        }
+
+        let mut report = RetentionReport::default();
+        purge_logs(&dir, 1, &mut report).unwrap();
+        let mut removed = report.log_files.clone();
+        removed.sort();
+        let remaining: Vec<bool> = names.iter().map(|n| dir.join(n).exists()).collect();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(removed, vec!["crash-20260101-000000-42.json", "desktop.log.1", "local-api.log.3"]);
+        assert_eq!(remaining, vec![true, false, false, false, true, true]);
+    }
+}
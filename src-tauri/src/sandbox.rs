@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+/// Paths the sandboxed sidecar may touch. Everything else on the filesystem
+/// is denied.
+pub struct SandboxPaths {
+    pub runtime_binary: PathBuf,
+    pub resource_dir: PathBuf,
+    pub script_dir: Option<PathBuf>,
+    /// `LOCAL_API_DATA_DIR`, the log dir and the socket dir.
+    pub write_dirs: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "kebab-case")]
+pub enum SandboxStatus {
+    Disabled,
+    /// Requested, but this platform or kernel has no Landlock.
+    Unsupported { reason: String },
+    /// Landlock rules are applied at spawn. Older kernels enforce a subset
+    /// of them; `abi` says which version the kernel speaks.
+    Enforced { abi: i32 },
+    Failed { error: String },
+}
+
+/// Read-only system paths a dynamically linked runtime needs to exec,
+/// resolve DNS, verify TLS peers and read its own process info.
+/// `/etc/resolv.conf` and the CA bundles are often symlinks out of `/etc`, so
+/// their usual targets are listed too; paths that do not exist are skipped.
+#[cfg(target_os = "linux")]
+const SYSTEM_READ_PATHS: &[&str] = &[
+    "/usr",
+    "/lib",
+    "/lib64",
+    "/lib32",
+    "/etc",
+    "/run/systemd/resolve",
+    "/run/NetworkManager",
+    "/run/resolvconf",
+    "/var/lib/ca-certificates",
+    "/proc",
+    "/sys/fs/cgroup",
+    "/sys/devices/system/cpu",
+    "/nix/store",
+    "/dev/urandom",
+    "/dev/random",
+];
+#[cfg(target_os = "linux")]
+const SYSTEM_READ_WRITE_PATHS: &[&str] = &["/dev/null"];
+
+/// Where the runtime's own files live: the install prefix for
+/// `<prefix>/bin/node` (so `<prefix>/lib` is covered), else its directory.
+fn runtime_root(binary: &Path) -> Option<PathBuf> {
+    let binary = binary.canonicalize().unwrap_or_else(|_| binary.to_path_buf());
+    let dir = binary.parent()?;
+    match dir.file_name().and_then(|n| n.to_str()) {
+        Some("bin") => dir.parent().map(Path::to_path_buf),
+        _ => Some(dir.to_path_buf()),
+    }
+}
+
+/// Landlock ABI version of the running kernel, or the reason there is none.
+#[cfg(target_os = "linux")]
+fn landlock_abi() -> Result<i32, String> {
+    const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+    // SAFETY: a version query with a null attr and zero size is the documented probe.
+    let abi = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<libc::c_void>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    if abi > 0 {
+        Ok(abi as i32)
+    } else {
+        let err = std::io::Error::last_os_error();
+        Err(match err.raw_os_error() {
+            Some(libc::ENOSYS) => "kernel built without Landlock (needs Linux 5.13+)".to_string(),
+            Some(libc::EOPNOTSUPP) => "Landlock is disabled at boot".to_string(),
+            _ => format!("Landlock probe failed: {err}"),
+        })
+    }
+}
+
+/// Build the ruleset now and enforce it in the child between fork and exec,
+/// where only `restrict_self` runs, so nothing allocates after the fork.
+#[cfg(target_os = "linux")]
+pub fn apply(cmd: &mut Command, paths: &SandboxPaths) -> SandboxStatus {
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, ABI,
+    };
+    use std::os::unix::process::CommandExt;
+
+    let abi = match landlock_abi() {
+        Ok(abi) => abi,
+        Err(reason) => return SandboxStatus::Unsupported { reason },
+    };
+    // Target the newest ABI we know; the crate drops rights older kernels lack.
+    let target = ABI::V5;
+    let mut read_dirs: Vec<PathBuf> = vec![paths.resource_dir.clone()];
+    read_dirs.extend(paths.script_dir.clone());
+    read_dirs.extend(runtime_root(&paths.runtime_binary));
+
+    let ruleset = Ruleset::default()
+        .handle_access(AccessFs::from_all(target))
+        .and_then(|r| r.create())
+        .and_then(|r| r.add_rules(path_beneath_rules(SYSTEM_READ_PATHS, AccessFs::from_read(target))))
+        .and_then(|r| r.add_rules(path_beneath_rules(&read_dirs, AccessFs::from_read(target))))
+        .and_then(|r| r.add_rules(path_beneath_rules(SYSTEM_READ_WRITE_PATHS, AccessFs::from_all(target))))
+        .and_then(|r| r.add_rules(path_beneath_rules(&paths.write_dirs, AccessFs::from_all(target))));
+    let ruleset = match ruleset {
+        Ok(ruleset) => ruleset,
+        Err(e) => return SandboxStatus::Failed { error: e.to_string() },
+    };
+
+    let mut ruleset = Some(ruleset);
+    // SAFETY: restrict_self only issues prctl and landlock_restrict_self.
+    unsafe {
+        cmd.pre_exec(move || match ruleset.take() {
+            Some(ruleset) => ruleset
+                .restrict_self()
+                .map(|_| ())
+                .map_err(|_| std::io::Error::from_raw_os_error(libc::EPERM)),
+            None => Ok(()),
+        });
+    }
+    SandboxStatus::Enforced { abi }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply(_cmd: &mut Command, _paths: &SandboxPaths) -> SandboxStatus {
+    SandboxStatus::Unsupported {
+        reason: "sidecar sandboxing is only available on Linux".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_root_covers_install_prefix() {
+        assert_eq!(
+            runtime_root(Path::new("/nonexistent/nvm/v20.11.1/bin/node")),
+            Some(PathBuf::from("/nonexistent/nvm/v20.11.1"))
+        );
+        assert_eq!(
+            runtime_root(Path::new("/nonexistent/.deno/deno")),
+            Some(PathBuf::from("/nonexistent/.deno"))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn resolv_conf_target_is_readable() {
+        let Ok(target) = Path::new("/etc/resolv.conf").canonicalize() else {
+            return;
+        };
+        assert!(
+            SYSTEM_READ_PATHS.iter().any(|dir| target.starts_with(dir)),
+            "{} is outside every read rule",
+            target.display()
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{ChildStderr, ChildStdout};
+use std::sync::mpsc::{self, Receiver};
+
+use serde::Deserialize;
+
+use crate::local_api_metrics::RequestSample;
+
+/// Must match `HANDSHAKE_PREFIX` in sidecar/local-api-server.mjs.
+const HANDSHAKE_PREFIX: &str = "LOCAL_API_HANDSHAKE ";
+/// Must match `METRIC_PREFIX` in sidecar/local-api-server.mjs.
+const METRIC_PREFIX: &str = "LOCAL_API_METRIC ";
+
+/// First line the sidecar prints once it has bound (or failed to bind) its
+/// listener. Exactly one of `port` / `socket` / `error` is set.
+#[derive(Debug, Deserialize)]
+pub struct Handshake {
+    pub port: Option<u16>,
+    pub socket: Option<String>,
+    pub error: Option<String>,
+    pub message: Option<String>,
+}
+
+fn parse_handshake(line: &str) -> Option<Handshake> {
+    let payload = line.trim_end().strip_prefix(HANDSHAKE_PREFIX)?;
+    serde_json::from_str(payload).ok()
+}
+
+fn parse_metric(line: &str) -> Option<RequestSample> {
+    let payload = line.trim_end().strip_prefix(METRIC_PREFIX)?;
+    serde_json::from_str(payload).ok()
+}
+
+/// Drain the sidecar's stdout on a background thread. The handshake line is
+/// delivered through the returned channel and per-request metric lines go to
+/// `on_metric`; every other line goes to `sink` unchanged. The channel
+/// disconnects without a value if stdout closes before a handshake arrives
+/// (the process died during startup).
+pub fn forward_stdout(
+    stdout: ChildStdout,
+    sink: impl FnMut(&[u8]) + Send + 'static,
+    mut on_metric: impl FnMut(RequestSample) + Send + 'static,
+) -> Receiver<Handshake> {
+    let (tx, rx) = mpsc::channel();
+    let mut handshake_tx = Some(tx);
+    forward_lines(stdout, sink, move |line| {
+        if handshake_tx.is_none() {
+            if !line.starts_with(METRIC_PREFIX.as_bytes()) {
+                return false;
+            }
+            if let Some(sample) = parse_metric(&String::from_utf8_lossy(line)) {
+                on_metric(sample);
+            }
+            return true;
+        }
+        match parse_handshake(&String::from_utf8_lossy(line)) {
+            Some(handshake) => {
+                if let Some(tx) = handshake_tx.take() {
+                    let _ = tx.send(handshake);
+                }
+                true
+            }
+            None => false,
+        }
+    });
+    rx
+}
+
+/// Drain the sidecar's stderr into `sink` line by line.
+pub fn forward_stderr(stderr: ChildStderr, sink: impl FnMut(&[u8]) + Send + 'static) {
+    forward_lines(stderr, sink, |_| false);
+}
+
+/// Whole lines only, so a rotation never splits one across two files.
+/// `intercept` returns true for lines it consumed.
+fn forward_lines<R: Read + Send + 'static>(
+    source: R,
+    mut sink: impl FnMut(&[u8]) + Send + 'static,
+    mut intercept: impl FnMut(&[u8]) -> bool + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(source);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if !intercept(&line) {
+                sink(&line);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_handshake, parse_metric};
+
+    #[test]
+    fn parses_port_handshake() {
+        let handshake = parse_handshake("LOCAL_API_HANDSHAKE {\"port\":46123,\"pid\":42}\n").unwrap();
+        assert_eq!(handshake.port, Some(46123));
+        assert!(handshake.error.is_none());
+    }
+
+    #[test]
+    fn parses_socket_handshake() {
+        let handshake =
+            parse_handshake("LOCAL_API_HANDSHAKE {\"socket\":\"/tmp/run/local-api.sock\",\"pid\":42}").unwrap();
+        assert_eq!(handshake.socket.as_deref(), Some("/tmp/run/local-api.sock"));
+        assert!(handshake.port.is_none());
+    }
+
+    #[test]
+    fn parses_bind_error_handshake() {
+        let handshake = parse_handshake(
+            "LOCAL_API_HANDSHAKE {\"error\":\"EADDRINUSE\",\"message\":\"listen EADDRINUSE\",\"port\":46123}",
+        )
+        .unwrap();
+        assert_eq!(handshake.error.as_deref(), Some("EADDRINUSE"));
+    }
+
+    #[test]
+    fn ignores_regular_log_lines() {
+        assert!(parse_handshake("[local-api] listening on http://127.0.0.1:46123").is_none());
+        assert!(parse_handshake("LOCAL_API_HANDSHAKE not-json").is_none());
+    }
+
+    #[test]
+    fn parses_request_metric() {
+        let sample = parse_metric(
+            "LOCAL_API_METRIC {\"route\":\"/api/rss-proxy\",\"upstream\":\"local\",\"method\":\"GET\",\"status\":200,\"durationMs\":84,\"bytes\":5120}\n",
+        )
+        .unwrap();
+        assert_eq!(sample.route, "/api/rss-proxy");
+        assert_eq!(sample.status, 200);
+        assert_eq!(sample.duration_ms, 84.0);
+        assert!(parse_metric("LOCAL_API_METRIC {}").is_none());
+    }
+}
@@ -0,0 +1,317 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::logging::{iso_timestamp, now_ms};
+
+const LOCK_FILE: &str = "local-api.lock";
+/// How long a leftover sidecar gets to exit after SIGTERM before SIGKILL.
+const ORPHAN_TERM_GRACE: Duration = Duration::from_secs(3);
+
+/// Written next to the socket when the sidecar is spawned and removed when it
+/// is stopped. If the host dies without stopping it, the next launch uses
+/// this to find and terminate the leftover process.
+#[derive(Serialize, Deserialize)]
+struct SidecarLock {
+    pid: u32,
+    host_pid: u32,
+    /// Script argument exactly as passed to the runtime.
+    script: String,
+    /// `LOCAL_API_DATA_DIR` exactly as passed to the sidecar.
+    data_dir: String,
+    started_at: String,
+}
+
+fn lock_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?
+        .join("run");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    Ok(dir.join(LOCK_FILE))
+}
+
+pub fn record(app: &AppHandle, pid: u32, script: &str, data_dir: &str) {
+    let lock = SidecarLock {
+        pid,
+        host_pid: std::process::id(),
+        script: script.to_string(),
+        data_dir: data_dir.to_string(),
+        started_at: iso_timestamp(now_ms()),
+    };
+    let result = lock_path(app).and_then(|path| {
+        let json = serde_json::to_string_pretty(&lock).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    });
+    if let Err(err) = result {
+        log_event!(app, Warn, "failed to record sidecar lock file", pid = pid, error = err);
+    }
+}
+
+pub fn clear(app: &AppHandle) {
+    if let Ok(path) = lock_path(app) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// The process's command line and, where the OS exposes it, its environment.
+struct ProcessIdentity {
+    command_line: String,
+    environment: Option<String>,
+}
+
+#[cfg(target_os = "linux")]
+fn process_identity(pid: u32) -> Option<ProcessIdentity> {
+    let read = |name: &str| {
+        fs::read(format!("/proc/{pid}/{name}"))
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(&bytes).replace('\0', "\n"))
+    };
+    Some(ProcessIdentity {
+        command_line: read("cmdline")?,
+        environment: read("environ"),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn process_identity(pid: u32) -> Option<ProcessIdentity> {
+    let ps = |args: &[&str]| {
+        std::process::Command::new("ps")
+            .args(args)
+            .args(["-p", &pid.to_string()])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    Some(ProcessIdentity {
+        command_line: ps(&["-ww", "-o", "command="])?,
+        // -E appends the environment to the command column for our own processes.
+        environment: ps(&["-wwE", "-o", "command="]),
+    })
+}
+
+#[cfg(windows)]
+fn process_identity(pid: u32) -> Option<ProcessIdentity> {
+    use std::os::windows::process::CommandExt;
+    let output = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            &format!("(Get-CimInstance Win32_Process -Filter \"ProcessId={pid}\").CommandLine"),
+        ])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    let command_line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if command_line.is_empty() {
+        return None;
+    }
+    Some(ProcessIdentity {
+        command_line,
+        environment: None,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn process_identity(_pid: u32) -> Option<ProcessIdentity> {
+    None
+}
+
+/// Whether `needle` appears as a whole entry: bounded by the ends, whitespace
+/// (NUL separators are turned into newlines) or quotes. Paths may contain
+/// spaces, so the text is not split; `/data` must not match `/data-other`.
+fn contains_entry(haystack: &str, needle: &str) -> bool {
+    let is_boundary = |c: Option<char>| c.is_none_or(|c| c.is_whitespace() || c == '"');
+    haystack.match_indices(needle).any(|(i, m)| {
+        is_boundary(haystack[..i].chars().next_back()) && is_boundary(haystack[i + m.len()..].chars().next())
+    })
+}
+
+/// The pid could have been reused since the lock was written, so only treat
+/// it as ours if it runs our script against our data dir. Windows does not
+/// expose another process's environment, so there the script must match alone.
+fn is_our_sidecar(lock: &SidecarLock, identity: &ProcessIdentity) -> bool {
+    if !contains_entry(&identity.command_line, &lock.script) {
+        return false;
+    }
+    match &identity.environment {
+        Some(env) => contains_entry(env, &format!("LOCAL_API_DATA_DIR={}", lock.data_dir)),
+        None => cfg!(windows),
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the pid exists and we may signal it.
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    process_identity(pid).is_some()
+}
+
+#[cfg(unix)]
+fn terminate(pid: u32) -> Result<(), String> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(format!("SIGTERM failed: {}", std::io::Error::last_os_error()));
+    }
+    let deadline = Instant::now() + ORPHAN_TERM_GRACE;
+    while Instant::now() < deadline {
+        if !process_alive(pid) {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } != 0 && process_alive(pid) {
+        return Err(format!("SIGKILL failed: {}", std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn terminate(pid: u32) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    // Its console-less parent is gone, so there is nobody to ask nicely.
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .status()
+        .map_err(|e| format!("taskkill failed to run: {e}"))?;
+    let deadline = Instant::now() + ORPHAN_TERM_GRACE;
+    while process_alive(pid) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    if process_alive(pid) {
+        return Err(format!("taskkill exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Terminate a sidecar left running by a previous host that crashed, so it
+/// releases its port and stale token before we spawn a new one.
+pub fn reap_orphan(app: &AppHandle) {
+    let Ok(path) = lock_path(app) else {
+        return;
+    };
+    let Ok(json) = fs::read_to_string(&path) else {
+        return;
+    };
+    let lock: SidecarLock = match serde_json::from_str(&json) {
+        Ok(lock) => lock,
+        Err(_) => {
+            let _ = fs::remove_file(&path);
+            return;
+        }
+    };
+
+    if lock.host_pid != std::process::id() && process_alive(lock.host_pid) {
+        // Another instance may still own it; leave both alone.
+        log_event!(
+            app,
+            Warn,
+            "sidecar lock belongs to a running host; not touching it",
+            pid = lock.pid,
+            host_pid = lock.host_pid
+        );
+        return;
+    }
+
+    let orphan = process_alive(lock.pid)
+        && process_identity(lock.pid).is_some_and(|identity| is_our_sidecar(&lock, &identity));
+    if orphan {
+        match terminate(lock.pid) {
+            Ok(()) => log_event!(
+                app,
+                Warn,
+                "terminated orphaned local API sidecar from a previous run",
+                pid = lock.pid,
+                started_at = lock.started_at
+            ),
+            Err(err) => {
+                log_event!(
+                    app,
+                    Error,
+                    "failed to terminate orphaned local API sidecar",
+                    pid = lock.pid,
+                    error = err
+                );
+                return;
+            }
+        }
+    }
+    let _ = fs::remove_file(&path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock() -> SidecarLock {
+        SidecarLock {
+            pid: 4242,
+            host_pid: 4241,
+            script: "/opt/wm/sidecar/local-api-server.mjs".into(),
+            data_dir: "/home/u/.local/share/wm/logs".into(),
+            started_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn identifies_sidecar_by_script_and_data_dir() {
+        let ours = ProcessIdentity {
+            command_line: "node\n/opt/wm/sidecar/local-api-server.mjs\n".into(),
+            environment: Some("HOME=/home/u\nLOCAL_API_DATA_DIR=/home/u/.local/share/wm/logs\n".into()),
+        };
+        assert!(is_our_sidecar(&lock(), &ours));
+
+        let other_profile = ProcessIdentity {
+            environment: Some("LOCAL_API_DATA_DIR=/tmp/other\n".into()),
+            ..ours
+        };
+        assert!(!is_our_sidecar(&lock(), &other_profile));
+
+        let reused_pid = ProcessIdentity {
+            command_line: "/usr/bin/python3 server.py".into(),
+            environment: Some("LOCAL_API_DATA_DIR=/home/u/.local/share/wm/logs\n".into()),
+        };
+        assert!(!is_our_sidecar(&lock(), &reused_pid));
+    }
+
+    #[test]
+    fn data_dir_and_script_must_match_whole_entries() {
+        let ours = ProcessIdentity {
+            command_line: "node\n/opt/wm/sidecar/local-api-server.mjs\n".into(),
+            environment: Some("LOCAL_API_DATA_DIR=/home/u/.local/share/wm/logs\n".into()),
+        };
+        let sibling_dir = ProcessIdentity {
+            environment: Some("LOCAL_API_DATA_DIR=/home/u/.local/share/wm/logs-other\n".into()),
+            ..ours
+        };
+        assert!(!is_our_sidecar(&lock(), &sibling_dir));
+
+        let sibling_script = ProcessIdentity {
+            command_line: "node /opt/wm/sidecar/local-api-server.mjs.bak".into(),
+            environment: Some("LOCAL_API_DATA_DIR=/home/u/.local/share/wm/logs".into()),
+        };
+        assert!(!is_our_sidecar(&lock(), &sibling_script));
+
+        let mut spaced = lock();
+        spaced.data_dir = "/Users/u/Library/Application Support/wm/logs".into();
+        let macos = ProcessIdentity {
+            command_line: "node /opt/wm/sidecar/local-api-server.mjs".into(),
+            environment: Some(
+                "node /opt/wm/sidecar/local-api-server.mjs LOCAL_API_DATA_DIR=/Users/u/Library/Application Support/wm/logs HOME=/Users/u"
+                    .into(),
+            ),
+        };
+        assert!(is_our_sidecar(&spaced, &macos));
+    }
+}
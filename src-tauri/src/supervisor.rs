@@ -0,0 +1,297 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::logging::{self, Level};
+use crate::resource_limits::{self, LimitBreach};
+use crate::{announce_local_api_endpoint, require_trusted_window, start_local_api, LocalApiState};
+
+pub const SUPERVISOR_EVENT: &str = "local-api-supervisor";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const BACKOFF_BASE_MS: u64 = 500;
+const BACKOFF_MAX_MS: u64 = 30_000;
+/// A child that stayed up this long is considered healthy again, so a later
+/// crash starts the backoff from scratch instead of counting toward the breaker.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+const CRASH_LOOP_THRESHOLD: u32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SupervisorPhase {
+    Idle,
+    Running,
+    Crashed,
+    Restarting,
+    CrashLoop,
+    Stopped,
+}
+
+#[derive(Clone, Serialize)]
+pub struct SupervisorStatus {
+    pub phase: SupervisorPhase,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub consecutive_failures: u32,
+    pub last_exit: Option<String>,
+    /// Set when the last exit was caused by a configured resource limit.
+    pub last_limit_breach: Option<LimitBreach>,
+    pub next_retry_ms: Option<u64>,
+}
+
+pub struct SupervisorState {
+    status: Mutex<SupervisorStatus>,
+}
+
+impl Default for SupervisorState {
+    fn default() -> Self {
+        SupervisorState {
+            status: Mutex::new(SupervisorStatus {
+                phase: SupervisorPhase::Idle,
+                pid: None,
+                restarts: 0,
+                consecutive_failures: 0,
+                last_exit: None,
+                last_limit_breach: None,
+                next_retry_ms: None,
+            }),
+        }
+    }
+}
+
+impl SupervisorState {
+    pub fn snapshot(&self) -> SupervisorStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn phase(&self) -> SupervisorPhase {
+        self.snapshot().phase
+    }
+
+    /// Stop supervising (app exit). The sidecar is then torn down by
+    /// `stop_local_api` without the supervisor racing to bring it back.
+    pub fn shutdown(&self, app: &AppHandle) {
+        if self.phase() == SupervisorPhase::Stopped {
+            return;
+        }
+        transition(app, |s| {
+            s.phase = SupervisorPhase::Stopped;
+            s.next_retry_ms = None;
+        });
+    }
+}
+
+/// A user-initiated restart clears the crash-loop breaker and resumes
+/// watching the new child. Ignored once the app is shutting down.
+pub fn resume_after_manual_restart(app: &AppHandle) {
+    if app.state::<SupervisorState>().phase() == SupervisorPhase::Stopped {
+        return;
+    }
+    let pid = current_pid(app);
+    transition(app, |s| {
+        s.phase = if pid.is_some() {
+            SupervisorPhase::Running
+        } else {
+            SupervisorPhase::Idle
+        };
+        s.pid = pid;
+        s.consecutive_failures = 0;
+        s.next_retry_ms = None;
+    });
+}
+
+fn backoff_delay(consecutive_failures: u32) -> Duration {
+    let exp = consecutive_failures.saturating_sub(1).min(16);
+    Duration::from_millis((BACKOFF_BASE_MS << exp).min(BACKOFF_MAX_MS))
+}
+
+/// Mutate the status, then log and emit the new snapshot so the UI and
+/// desktop.log see every transition in the same order.
+fn transition(app: &AppHandle, change: impl FnOnce(&mut SupervisorStatus)) {
+    let Some(state) = app.try_state::<SupervisorState>() else {
+        return;
+    };
+    let snapshot = {
+        let mut status = state.status.lock().unwrap_or_else(|e| e.into_inner());
+        change(&mut status);
+        status.clone()
+    };
+    logging::log(
+        app,
+        if snapshot.phase == SupervisorPhase::CrashLoop { Level::Error } else { Level::Info },
+        module_path!(),
+        "supervisor phase changed",
+        &[
+            ("phase", json!(snapshot.phase)),
+            ("pid", json!(snapshot.pid)),
+            ("restarts", json!(snapshot.restarts)),
+            ("consecutive_failures", json!(snapshot.consecutive_failures)),
+            ("last_exit", json!(snapshot.last_exit)),
+            ("last_limit_breach", json!(snapshot.last_limit_breach)),
+            ("next_retry_ms", json!(snapshot.next_retry_ms)),
+        ],
+    );
+    let _ = app.emit(SUPERVISOR_EVENT, &snapshot);
+}
+
+/// Reap the child if it has exited. Returns the exit description, how long
+/// it had been running, and the resource limit that killed it, if any.
+fn reap_exited_child(app: &AppHandle) -> Option<(String, Duration, Option<LimitBreach>)> {
+    let state = app.state::<LocalApiState>();
+    let mut slot = state.child.lock().ok()?;
+    // A child that dies before its handshake is reported by `start_local_api`.
+    if *state.starting.lock().unwrap_or_else(|e| e.into_inner()) {
+        return None;
+    }
+    let child = slot.as_mut()?;
+    let (exit, status) = match child.try_wait() {
+        Ok(Some(status)) => (status.to_string(), Some(status)),
+        Ok(None) => return None,
+        Err(e) => (format!("wait failed: {e}"), None),
+    };
+    slot.take();
+    drop(slot);
+    let breach = resource_limits::take_breach(app, status.as_ref());
+
+    let uptime = state
+        .started_at
+        .lock()
+        .ok()
+        .and_then(|mut g| g.take())
+        .map(|t| t.elapsed())
+        .unwrap_or_default();
+    if let Ok(mut port_slot) = state.port.lock() {
+        *port_slot = None;
+    }
+    if let Ok(mut socket_slot) = state.socket.lock() {
+        *socket_slot = None;
+    }
+    Some((exit, uptime, breach))
+}
+
+fn current_pid(app: &AppHandle) -> Option<u32> {
+    let state = app.state::<LocalApiState>();
+    let slot = state.child.lock().ok()?;
+    slot.as_ref().map(|c| c.id())
+}
+
+fn restart_with_backoff(app: &AppHandle) {
+    loop {
+        let failures = app.state::<SupervisorState>().snapshot().consecutive_failures;
+        if failures >= CRASH_LOOP_THRESHOLD {
+            transition(app, |s| {
+                s.phase = SupervisorPhase::CrashLoop;
+                s.next_retry_ms = None;
+            });
+            return;
+        }
+
+        let delay = backoff_delay(failures);
+        transition(app, |s| {
+            s.phase = SupervisorPhase::Restarting;
+            s.next_retry_ms = Some(delay.as_millis() as u64);
+        });
+        std::thread::sleep(delay);
+        if app.state::<SupervisorState>().phase() != SupervisorPhase::Restarting {
+            return;
+        }
+
+        match start_local_api(app) {
+            Ok(()) => {
+                let pid = current_pid(app);
+                transition(app, |s| {
+                    s.phase = SupervisorPhase::Running;
+                    s.pid = pid;
+                    s.restarts += 1;
+                    s.next_retry_ms = None;
+                });
+                // The respawn may land on another port; the frontend must follow it.
+                if let Err(err) = announce_local_api_endpoint(app) {
+                    log_event!(app, Warn, "supervised restart did not announce its endpoint", error = err);
+                }
+                return;
+            }
+            Err(err) => {
+                transition(app, |s| {
+                    s.consecutive_failures += 1;
+                    s.last_exit = Some(format!("restart failed: {err}"));
+                });
+            }
+        }
+    }
+}
+
+/// Watch the sidecar child and bring it back when it dies on its own.
+/// Intentional stops go through `stop_local_api`, which takes the child out
+/// of `LocalApiState`, so the supervisor only ever reaps unexpected exits.
+pub fn spawn_supervisor(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let phase = app.state::<SupervisorState>().phase();
+        if phase == SupervisorPhase::Stopped {
+            return;
+        }
+        if phase == SupervisorPhase::Idle {
+            // Initial spawn happens in setup; start watching once a child exists.
+            if let Some(pid) = current_pid(&app) {
+                transition(&app, |s| {
+                    s.phase = SupervisorPhase::Running;
+                    s.pid = Some(pid);
+                });
+            }
+            continue;
+        }
+        if phase != SupervisorPhase::Running {
+            continue;
+        }
+        let Some((exit, uptime, breach)) = reap_exited_child(&app) else {
+            continue;
+        };
+        if let Some(limit) = breach {
+            log_event!(
+                &app,
+                Error,
+                "local API sidecar was killed for exceeding its resource limits",
+                limit = limit,
+                exit = exit
+            );
+        }
+
+        transition(&app, |s| {
+            s.phase = SupervisorPhase::Crashed;
+            s.pid = None;
+            s.last_exit = Some(exit);
+            s.last_limit_breach = breach;
+            s.consecutive_failures = if uptime >= STABLE_UPTIME {
+                1
+            } else {
+                s.consecutive_failures + 1
+            };
+        });
+        restart_with_backoff(&app);
+    });
+}
+
+#[tauri::command]
+pub fn get_local_api_supervisor_status(
+    webview: Webview,
+    state: tauri::State<'_, SupervisorState>,
+) -> Result<SupervisorStatus, String> {
+    require_trusted_window(webview.label())?;
+    Ok(state.snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        assert_eq!(backoff_delay(1), Duration::from_millis(500));
+        assert_eq!(backoff_delay(2), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(4), Duration::from_millis(4000));
+        assert_eq!(backoff_delay(20), Duration::from_millis(BACKOFF_MAX_MS));
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use reqwest::Url;
+use serde_json::{json, Value};
+use tauri::http::StatusCode;
+use tauri::{AppHandle, Manager, Webview};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::local_api_metrics::RequestSample;
+use crate::logging::{iso_timestamp, now_ms};
+use crate::redact;
+use crate::require_trusted_window;
+
+/// Most recent requests kept for export; older ones are dropped first.
+const MAX_ENTRIES: usize = 1000;
+/// Header values that are credentials no matter what they contain.
+const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-worldmonitor-key",
+    "x-relay-key",
+];
+const REDACTED: &str = "[REDACTED]";
+
+/// Redacted HAR entries for recent sidecar traffic.
+#[derive(Default)]
+pub struct TrafficLog {
+    entries: Mutex<VecDeque<Value>>,
+}
+
+fn har_headers(headers: &[(String, String)], redact: &impl Fn(&str) -> String) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                REDACTED.to_string()
+            } else {
+                redact(value)
+            };
+            json!({ "name": name, "value": value })
+        })
+        .collect()
+}
+
+/// One HAR 1.2 entry. Bodies are not captured, only their sizes. The sidecar
+/// only knows the total time, so it is all reported as `wait`.
+fn har_entry(sample: &RequestSample, redact: impl Fn(&str) -> String) -> Value {
+    let url = redact(&sample.url);
+    let query: Vec<Value> = Url::parse(&url)
+        .map(|u| {
+            u.query_pairs()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect()
+        })
+        .unwrap_or_default();
+    let http_version = if sample.http_version.is_empty() {
+        "HTTP/1.1"
+    } else {
+        sample.http_version.as_str()
+    };
+    let mime_type = sample
+        .response_headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or("");
+    let status_text = StatusCode::from_u16(sample.status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("");
+    json!({
+        "startedDateTime": sample.started_at,
+        "time": sample.duration_ms,
+        "request": {
+            "method": sample.method,
+            "url": url,
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": har_headers(&sample.request_headers, &redact),
+            "queryString": query,
+            "headersSize": -1,
+            "bodySize": sample.request_bytes,
+        },
+        "response": {
+            "status": sample.status,
+            "statusText": status_text,
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": har_headers(&sample.response_headers, &redact),
+            "content": { "size": sample.bytes, "mimeType": mime_type },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": sample.bytes,
+        },
+        "cache": {},
+        "timings": { "blocked": -1, "dns": -1, "connect": -1, "send": 0, "wait": sample.duration_ms, "receive": 0, "ssl": -1 },
+        "_route": sample.route,
+        "_upstream": sample.upstream,
+    })
+}
+
+/// Called for every request the sidecar reports.
+pub fn record(app: &AppHandle, sample: &RequestSample) {
+    let Some(log) = app.try_state::<TrafficLog>() else {
+        return;
+    };
+    if sample.url.is_empty() {
+        return;
+    }
+    let entry = har_entry(sample, redact::redact_log);
+    let mut entries = log.entries.lock().unwrap_or_else(|e| e.into_inner());
+    if entries.len() == MAX_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(entry);
+}
+
+fn har_document(entries: Vec<Value>) -> Value {
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "World Monitor", "version": env!("CARGO_PKG_VERSION") },
+            "pages": [],
+            "entries": entries,
+        }
+    })
+}
+
+/// Ask where to save, then write the captured traffic as a HAR file.
+/// Returns the saved path, or `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn export_local_api_har(webview: Webview, app: AppHandle) -> Result<Option<String>, String> {
+    require_trusted_window(webview.label())?;
+    let entries: Vec<Value> = app
+        .state::<TrafficLog>()
+        .entries
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect();
+    let count = entries.len();
+    let document = serde_json::to_vec_pretty(&har_document(entries))
+        .map_err(|e| format!("Failed to serialize HAR: {e}"))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let stamp: String = iso_timestamp(now_ms())
+            .chars()
+            .take(19)
+            .map(|c| if c == ':' { '-' } else { c })
+            .collect();
+        let Some(target) = app
+            .dialog()
+            .file()
+            .set_title("Export local API traffic")
+            .set_file_name(format!("world-monitor-local-api-{stamp}.har"))
+            .add_filter("HTTP Archive", &["har"])
+            .blocking_save_file()
+        else {
+            return Ok(None);
+        };
+        let path = target
+            .into_path()
+            .map_err(|e| format!("Unsupported save location: {e}"))?;
+        std::fs::write(&path, document).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        log_event!(&app, Info, "exported local API traffic as HAR", path = path.display().to_string(), entries = count);
+        Ok(Some(path.display().to_string()))
+    })
+    .await
+    .map_err(|e| format!("HAR export task failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_redacted_har_entries() {
+        let sample = RequestSample {
+            route: "/api/[domain]/v1/[rpc]".into(),
+            upstream: "cloud".into(),
+            status: 200,
+            duration_ms: 84.0,
+            bytes: 512,
+            method: "GET".into(),
+            url: "http://127.0.0.1:46123/api/market/v1/quotes?symbols=SPY&token=s3cret".into(),
+            started_at: "2026-01-01T00:00:00.000Z".into(),
+            http_version: "HTTP/1.1".into(),
+            request_headers: vec![
+                ("Authorization".into(), "Bearer abc".into()),
+                ("X-Trace".into(), "s3cret-trace".into()),
+            ],
+            request_bytes: 0,
+            response_headers: vec![("content-type".into(), "application/json".into())],
+            fallback: None,
+        };
+        let entry = har_entry(&sample, |text| text.replace("s3cret", "[REDACTED]"));
+
+        assert_eq!(
+            entry["request"]["url"],
+            "http://127.0.0.1:46123/api/market/v1/quotes?symbols=SPY&token=[REDACTED]"
+        );
+        assert_eq!(entry["request"]["headers"][0]["value"], REDACTED);
+        assert_eq!(entry["request"]["headers"][1]["value"], "[REDACTED]-trace");
+        assert_eq!(entry["request"]["queryString"][0], json!({ "name": "symbols", "value": "SPY" }));
+        assert_eq!(entry["response"]["statusText"], "OK");
+        assert_eq!(entry["response"]["content"]["mimeType"], "application/json");
+        assert_eq!(entry["timings"]["wait"], 84.0);
+        assert_eq!(entry["_upstream"], "cloud");
+    }
+}
@@ -31,6 +31,7 @@ import { initI18n } from '@/services/i18n';
 
 import { fetchBootstrapData } from '@/services/bootstrap';
 import { DesktopUpdater } from '@/app/desktop-updater';
+import { CrashNotice } from '@/app/crash-notice';
 import { CountryIntelManager } from '@/app/country-intel';
 import { SearchManager } from '@/app/search-manager';
 import { RefreshScheduler } from '@/app/refresh-scheduler';
@@ -53,6 +54,7 @@ export class App {
   private countryIntel: CountryIntelManager;
   private refreshScheduler: RefreshScheduler;
   private desktopUpdater: DesktopUpdater;
+  private crashNotice: CrashNotice;
 
   private modules: { destroy(): void }[] = [];
   private unsubAiFlow: (() => void) | null = null;
@@ -261,6 +263,7 @@ export class App {
     this.refreshScheduler = new RefreshScheduler(this.state);
     this.countryIntel = new CountryIntelManager(this.state);
     this.desktopUpdater = new DesktopUpdater(this.state);
+    this.crashNotice = new CrashNotice(this.state);
 
     this.dataLoader = new DataLoaderManager(this.state, {
       renderCriticalBanner: (postures) => this.panelLayout.renderCriticalBanner(postures),
@@ -292,6 +295,7 @@ export class App {
 
     // Track destroy order (reverse of init)
     this.modules = [
+      this.crashNotice,
       this.desktopUpdater,
       this.panelLayout,
       this.countryIntel,
@@ -411,6 +415,7 @@ export class App {
     // Phase 8: Deep links + update checks
     this.handleDeepLinks();
     this.desktopUpdater.init();
+    this.crashNotice.init();
 
     // Analytics
     trackEvent('wm_app_loaded', {
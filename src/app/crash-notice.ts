@@ -0,0 +1,73 @@
+import type { AppContext, AppModule } from '@/app/app-context';
+import { tryInvokeTauri } from '@/services/tauri-bridge';
+import { escapeHtml } from '@/utils/sanitize';
+
+interface PreviousCrash {
+  id: string;
+  path: string;
+  report: {
+    timestamp: string;
+    version: string;
+    message: string;
+  };
+}
+
+/** Offers the host's crash report when the previous session panicked. */
+export class CrashNotice implements AppModule {
+  private ctx: AppContext;
+
+  constructor(ctx: AppContext) {
+    this.ctx = ctx;
+  }
+
+  init(): void {
+    if (!this.ctx.isDesktopApp || this.ctx.isDestroyed) return;
+    void this.checkPreviousCrash();
+  }
+
+  destroy(): void {
+    document.querySelector('.crash-toast')?.remove();
+  }
+
+  private async checkPreviousCrash(): Promise<void> {
+    const crash = await tryInvokeTauri<PreviousCrash | null>('get_previous_crash');
+    if (!crash || this.ctx.isDestroyed) return;
+    this.showCrashToast(crash);
+  }
+
+  private showCrashToast(crash: PreviousCrash): void {
+    const toast = document.createElement('div');
+    toast.className = 'update-toast crash-toast';
+    toast.innerHTML = `
+      <div class="update-toast-icon">
+        <svg width="20" height="20" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
+          <path d="M10.29 3.86 1.82 18a2 2 0 0 0 1.71 3h16.94a2 2 0 0 0 1.71-3L13.71 3.86a2 2 0 0 0-3.42 0z"/>
+          <line x1="12" y1="9" x2="12" y2="13"/>
+          <line x1="12" y1="17" x2="12.01" y2="17"/>
+        </svg>
+      </div>
+      <div class="update-toast-body">
+        <div class="update-toast-title">World Monitor closed unexpectedly</div>
+        <div class="update-toast-detail" title="${escapeHtml(crash.report.message)}">v${escapeHtml(crash.report.version)} · ${escapeHtml(crash.report.timestamp)}</div>
+      </div>
+      <button class="update-toast-action" data-action="view">View Report</button>
+      <button class="update-toast-dismiss" data-action="dismiss" aria-label="Dismiss">×</button>
+    `;
+
+    toast.addEventListener('click', (e) => {
+      const action = (e.target as HTMLElement).closest<HTMLElement>('[data-action]')?.dataset.action;
+      if (action === 'view') {
+        void tryInvokeTauri<string>('open_crash_report', { id: crash.id });
+      } else if (action === 'dismiss') {
+        void tryInvokeTauri<void>('dismiss_previous_crash');
+        toast.classList.remove('visible');
+        setTimeout(() => toast.remove(), 300);
+      }
+    });
+
+    document.body.appendChild(toast);
+    requestAnimationFrame(() => {
+      requestAnimationFrame(() => toast.classList.add('visible'));
+    });
+  }
+}
@@ -11,6 +11,9 @@ const DEFAULT_LOCAL_API_PORT = 46123;
 const FORCE_DESKTOP_RUNTIME = import.meta.env.VITE_DESKTOP_RUNTIME === '1';
 
 let _resolvedPort: number | null = null;
+// Set when the sidecar uses socket transport; requests then go through the
+// host's wmapi:// bridge instead of a loopback port.
+let _bridgeBaseUrl: string | null = null;
 let _portPromise: Promise<number> | null = null;
 
 export async function resolveLocalApiPort(): Promise<number> {
@@ -19,6 +22,13 @@ export async function resolveLocalApiPort(): Promise<number> {
   _portPromise = (async () => {
     try {
       const { tryInvokeTauri } = await import('@/services/tauri-bridge');
+      const baseUrl = await tryInvokeTauri<string>('get_local_api_base_url');
+      if (baseUrl && !baseUrl.startsWith('http://127.0.0.1:')) {
+        _bridgeBaseUrl = normalizeBaseUrl(baseUrl);
+        // No port to learn; mark resolved so the fetch shim stops asking.
+        _resolvedPort = DEFAULT_LOCAL_API_PORT;
+        return _resolvedPort;
+      }
       const port = await tryInvokeTauri<number>('get_local_api_port');
       if (port && port > 0) {
         _resolvedPort = port;
@@ -103,6 +113,10 @@ export function getApiBaseUrl(): string {
     return normalizeBaseUrl(configuredBaseUrl);
   }
 
+  if (_bridgeBaseUrl) {
+    return _bridgeBaseUrl;
+  }
+
   return `http://127.0.0.1:${getLocalApiPort()}`;
 }
 
@@ -258,6 +272,18 @@ export function installRuntimeFetchPatch(): void {
   let localApiToken: string | null = null;
   let tokenFetchedAt = 0;
 
+  // The host pushes the new endpoint after `restart_local_api`, which may
+  // change both the port and the token; adopt them without a reload.
+  void import('@/services/tauri-bridge').then(({ listenTauri }) =>
+    listenTauri<{ port: number | null; base_url: string; token: string }>('local-api-restarted', ({ port, base_url, token }) => {
+      // A null port means the sidecar moved to socket transport.
+      _bridgeBaseUrl = port ? null : normalizeBaseUrl(base_url);
+      if (port && port > 0) _resolvedPort = port;
+      localApiToken = token || null;
+      tokenFetchedAt = token ? Date.now() : 0;
+    }),
+  ).catch(() => {});
+
   window.fetch = async (input: RequestInfo | URL, init?: RequestInit): Promise<Response> => {
     const target = getApiTargetFromRequestInput(input);
     const debug = localStorage.getItem('wm-debug-log') === '1';
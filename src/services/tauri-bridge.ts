@@ -1,4 +1,5 @@
 type TauriInvoke = <T>(command: string, payload?: Record<string, unknown>) => Promise<T>;
+type TauriTransformCallback = (callback: (event: { payload: unknown }) => void, once?: boolean) => number;
 
 function resolveInvokeBridge(): TauriInvoke | null {
   if (typeof window === 'undefined') {
@@ -44,3 +45,37 @@ export async function tryInvokeTauri<T>(
     return null;
   }
 }
+
+/**
+ * Subscribe to a Rust-emitted event without pulling in @tauri-apps/api.
+ * Mirrors what its `listen()` does through the internals bridge.
+ * Resolves to an unlisten function, or null outside the desktop runtime.
+ */
+export async function listenTauri<T>(
+  event: string,
+  handler: (payload: T) => void,
+): Promise<(() => void) | null> {
+  if (typeof window === 'undefined') return null;
+  const internals = (window as unknown as {
+    __TAURI_INTERNALS__?: { invoke?: TauriInvoke; transformCallback?: TauriTransformCallback };
+  }).__TAURI_INTERNALS__;
+  if (typeof internals?.invoke !== 'function' || typeof internals.transformCallback !== 'function') {
+    return null;
+  }
+
+  const invoke = internals.invoke;
+  try {
+    const handlerId = internals.transformCallback((e) => handler(e.payload as T));
+    const eventId = await invoke<number>('plugin:event|listen', {
+      event,
+      target: { kind: 'Any' },
+      handler: handlerId,
+    });
+    return () => {
+      void invoke('plugin:event|unlisten', { event, eventId }).catch(() => {});
+    };
+  } catch (error) {
+    console.warn(`[tauri-bridge] Listen failed: ${event}`, error);
+    return null;
+  }
+}
@@ -4,7 +4,7 @@ import { RuntimeConfigPanel } from '@/components/RuntimeConfigPanel';
 import { WorldMonitorTab } from '@/components/WorldMonitorTab';
 import { RUNTIME_FEATURES, loadDesktopSecrets } from '@/services/runtime-config';
 import { getApiBaseUrl, resolveLocalApiPort } from '@/services/runtime';
-import { tryInvokeTauri } from '@/services/tauri-bridge';
+import { invokeTauri, listenTauri, tryInvokeTauri } from '@/services/tauri-bridge';
 import { escapeHtml } from '@/utils/sanitize';
 import { initI18n, t } from '@/services/i18n';
 import { applyStoredTheme } from '@/utils/theme-manager';
@@ -56,6 +56,8 @@ function initTabs(): void {
       if (target === 'debug' && !diagnosticsInitialized) {
         diagnosticsInitialized = true;
         initDiagnostics();
+        initRouteTable();
+        initHostLogView();
       }
     });
   });
@@ -170,6 +172,10 @@ async function initSettingsWindow(): Promise<void> {
     void invokeDesktopAction('open_sidecar_log_file', t('modals.settingsWindow.openApiLog'));
   });
 
+  document.getElementById('exportDiagnosticsBtn')?.addEventListener('click', () => {
+    void invokeDesktopAction('export_diagnostics_bundle', t('modals.settingsWindow.diagnosticsExported'));
+  });
+
   initTabs();
 }
 
@@ -263,6 +269,15 @@ function initDiagnostics(): void {
     if (trafficCount) trafficCount.textContent = '(0)';
   });
 
+  document.getElementById('exportHarBtn')?.addEventListener('click', async () => {
+    try {
+      const path = await invokeTauri<string | null>('export_local_api_har');
+      if (path) setActionStatus(`${t('modals.settingsWindow.harExported')}: ${path}`, 'ok');
+    } catch {
+      setActionStatus(t('modals.settingsWindow.invokeFail', { command: 'export_local_api_har' }), 'error');
+    }
+  });
+
   let refreshInterval: ReturnType<typeof setInterval> | null = null;
 
   function startAutoRefresh(): void {
@@ -282,6 +297,147 @@ function initDiagnostics(): void {
   startAutoRefresh();
 }
 
+interface LocalApiRoute {
+  route: string;
+  priority: number;
+  module: string;
+  status: 'local' | 'cloud' | 'broken' | 'not-loaded';
+  error: string | null;
+  cloudPaths: string[];
+}
+
+interface LocalApiRouteTable {
+  apiDir: string;
+  cloudFallback: 'never' | 'always' | 'allowlist';
+  routes: LocalApiRoute[];
+  missing: string[];
+}
+
+function initRouteTable(): void {
+  const tableEl = document.getElementById('routeTable');
+  const routeCount = document.getElementById('routeCount');
+  const problemsOnly = document.getElementById('routeProblemsOnly') as HTMLInputElement | null;
+  const refreshBtn = document.getElementById('refreshRoutesBtn');
+  const probeBtn = document.getElementById('probeRoutesBtn');
+  if (!tableEl) return;
+
+  let table: LocalApiRouteTable | null = null;
+
+  function render(): void {
+    if (!tableEl || !table) return;
+    const broken = table.routes.filter((r) => r.status === 'broken').length;
+    if (routeCount) routeCount.textContent = `(${table.routes.length}${broken ? `, ${broken} broken` : ''})`;
+
+    const routes = problemsOnly?.checked
+      ? table.routes.filter((r) => r.status === 'cloud' || r.status === 'broken')
+      : table.routes;
+    const rows = routes.map((r) => {
+      const cls = r.status === 'broken' ? 'err' : r.status === 'cloud' ? 'cloud' : 'ok';
+      const detail = r.error ?? (r.cloudPaths.length ? r.cloudPaths.join(', ') : '');
+      return `<tr class="diag-${cls}" title="${escapeHtml(detail)}"><td>${escapeHtml(r.route)}</td><td>${escapeHtml(r.module)}</td><td>${escapeHtml(r.status)}</td><td>${r.priority}</td></tr>`;
+    });
+    // Paths nothing matched: the clearest sign of a missing handler.
+    for (const pathname of table.missing) {
+      rows.push(`<tr class="diag-err"><td>${escapeHtml(pathname)}</td><td>—</td><td>missing</td><td></td></tr>`);
+    }
+
+    if (rows.length === 0) {
+      const key = problemsOnly?.checked ? 'noRouteProblems' : 'noRoutes';
+      tableEl.innerHTML = `<p class="diag-empty">${t(`modals.settingsWindow.${key}`)}</p>`;
+      return;
+    }
+    tableEl.innerHTML = `<table class="diag-table"><thead><tr><th>${t('modals.settingsWindow.table.route')}</th><th>${t('modals.settingsWindow.table.module')}</th><th>${t('modals.settingsWindow.table.status')}</th><th>${t('modals.settingsWindow.table.priority')}</th></tr></thead><tbody>${rows.join('')}</tbody></table>`;
+  }
+
+  async function refresh(probe = false): Promise<void> {
+    try {
+      table = await invokeTauri<LocalApiRouteTable>('get_local_api_routes', { probe });
+      render();
+    } catch (error) {
+      table = null;
+      if (routeCount) routeCount.textContent = '';
+      tableEl!.innerHTML = `<p class="diag-empty">${escapeHtml(t('modals.settingsWindow.routesUnavailable', { error: String(error) }))}</p>`;
+    }
+  }
+
+  refreshBtn?.addEventListener('click', () => void refresh());
+  probeBtn?.addEventListener('click', () => void refresh(true));
+  problemsOnly?.addEventListener('change', render);
+  void refresh();
+}
+
+const HOST_LOG_MAX_LINES = 500;
+
+function initHostLogView(): void {
+  const sourceSelect = document.getElementById('hostLogSource') as HTMLSelectElement | null;
+  const levelSelect = document.getElementById('hostLogLevel') as HTMLSelectElement | null;
+  const filterInput = document.getElementById('hostLogFilter') as HTMLInputElement | null;
+  const liveToggle = document.getElementById('hostLogLive') as HTMLInputElement | null;
+  const refreshBtn = document.getElementById('refreshHostLogBtn');
+  const logEl = document.getElementById('hostLog');
+  if (!sourceSelect || !logEl) return;
+
+  let lines: string[] = [];
+  let streamingSource: string | null = null;
+
+  const currentFilter = () => ({
+    level: levelSelect?.value || null,
+    contains: filterInput?.value.trim() || null,
+  });
+
+  function render(): void {
+    if (!logEl) return;
+    const atBottom = logEl.scrollTop + logEl.clientHeight >= logEl.scrollHeight - 4;
+    logEl.textContent = lines.length ? lines.join('\n') : t('modals.settingsWindow.noLogLines');
+    if (atBottom) logEl.scrollTop = logEl.scrollHeight;
+  }
+
+  async function refresh(): Promise<void> {
+    const result = await tryInvokeTauri<string[]>('tail_log', {
+      source: sourceSelect!.value,
+      lines: HOST_LOG_MAX_LINES,
+      filter: currentFilter(),
+    });
+    lines = result ?? [];
+    render();
+    if (logEl) logEl.scrollTop = logEl.scrollHeight;
+  }
+
+  async function syncStream(): Promise<void> {
+    if (streamingSource) {
+      await tryInvokeTauri('unsubscribe_log_stream', { source: streamingSource });
+      streamingSource = null;
+    }
+    if (liveToggle?.checked) {
+      streamingSource = sourceSelect!.value;
+      await tryInvokeTauri('subscribe_log_stream', { source: streamingSource, filter: currentFilter() });
+    }
+  }
+
+  async function reload(): Promise<void> {
+    await refresh();
+    await syncStream();
+  }
+
+  void listenTauri<{ source: string; line: string }>('log-line', ({ source, line }) => {
+    if (source !== streamingSource) return;
+    lines.push(line);
+    if (lines.length > HOST_LOG_MAX_LINES) lines = lines.slice(-HOST_LOG_MAX_LINES);
+    render();
+  });
+
+  sourceSelect.addEventListener('change', () => void reload());
+  levelSelect?.addEventListener('change', () => void reload());
+  filterInput?.addEventListener('change', () => void reload());
+  liveToggle?.addEventListener('change', () => void syncStream());
+  refreshBtn?.addEventListener('click', () => void refresh());
+  window.addEventListener('beforeunload', () => {
+    if (streamingSource) void tryInvokeTauri('unsubscribe_log_stream', { source: streamingSource });
+  });
+
+  void reload();
+}
+
 // Signal main window that settings is open (suppresses alert popups)
 localStorage.setItem('wm-settings-open', '1');
 window.addEventListener('beforeunload', () => localStorage.removeItem('wm-settings-open'));